@@ -0,0 +1,10 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::core::cache::ResponseCache;
+
+pub fn clear_cache(path: &Path) -> Result<()> {
+    let removed = ResponseCache::new(path.join(".contexthub/cache")).clear()?;
+    println!("✓ Removed {} cached LLM result(s)", removed);
+    Ok(())
+}
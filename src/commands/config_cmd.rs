@@ -1,14 +1,41 @@
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
+use crate::commands::context::open_editor;
+use crate::core::llm::{DEFAULT_PROMPT_TEMPLATE, PROMPT_PLACEHOLDERS};
 use crate::utils::config::Config;
+use crate::utils::error::ContextHubError;
 
 pub fn show_config(config: &Config) -> Result<()> {
     println!("📋 Configuration\n");
     println!("Ollama:");
+    println!("  Provider:  {}", config.ollama.provider);
     println!("  Endpoint:  {}", config.ollama.endpoint);
+    if config.ollama.provider == "openai" {
+        let source = match (&config.ollama.api_key, config.ollama.resolved_api_key()) {
+            (Some(key), _) => format!("{} (set in config)", mask_secret(key)),
+            (None, Some(key)) => format!("{} (from CONTEXTHUB_API_KEY)", mask_secret(&key)),
+            (None, None) => "not set".to_string(),
+        };
+        println!("  API key:   {}", source);
+    }
     println!("  Model:     {}", config.ollama.model);
     println!("  Temperature: {}", config.ollama.temperature);
+    println!(
+        "  Max tokens:  {} (extraction), {} (overview)",
+        config.ollama.extraction_max_tokens(),
+        config.ollama.overview_max_tokens()
+    );
+    let model_default = |value: Option<String>| value.unwrap_or_else(|| "model default".to_string());
+    println!(
+        "  Context window: {} tokens{}",
+        config.ollama.context_window(),
+        if config.ollama.num_ctx.is_none() { " (Ollama default)" } else { "" }
+    );
+    println!("  Top p:       {}", model_default(config.ollama.top_p.map(|v| v.to_string())));
+    println!("  Repeat penalty: {}", model_default(config.ollama.repeat_penalty.map(|v| v.to_string())));
+    println!("  JSON mode:   {}", if config.ollama.force_json { "on" } else { "off" });
     println!();
     println!("Context:");
     println!(
@@ -19,25 +46,190 @@ pub fn show_config(config: &Config) -> Result<()> {
         "  Max tokens/commit:     {}",
         config.context.max_tokens_per_commit
     );
+    println!("  Max diff bytes:        {}", config.context.max_diff_bytes);
     println!("  TTL days:              {}", config.context.ttl_days);
+    println!("  Chaining depth:        {}", config.context.chaining_depth);
+    println!(
+        "  Incremental chaining:  {}",
+        if config.context.incremental_chaining { "on" } else { "off" }
+    );
+    println!("  Diff prefetch:         {}", config.context.diff_prefetch);
+    let overview = config.context.project_overview.trim();
+    if overview.is_empty() {
+        println!("  Project overview:      (not set)");
+    } else {
+        println!(
+            "  Project overview:      {} ({} chars)",
+            overview.lines().next().unwrap_or(""),
+            overview.chars().count()
+        );
+    }
     println!();
     println!("Git:");
     println!("  Auto sync:    {}", config.git.auto_sync);
     println!("  Hook enabled: {}", config.git.hook_enabled);
+    println!("  Diff context: {} lines", config.git.diff_context_lines);
+    println!("  Submodules:   {}", config.git.include_submodules);
+    println!("  Ignore whitespace: {}", config.git.ignore_whitespace);
 
     Ok(())
 }
 
-pub fn set_config_model(path: &PathBuf, config: &mut Config, model: String) -> Result<()> {
+/// Dotted keys whose values are masked unless `config get --reveal` is given
+const SECRET_KEYS: &[&str] = &["ollama.api_key"];
+
+/// Keep just enough of a secret to recognise it, e.g. `sk-…abcd`
+fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 12 {
+        return "…".to_string();
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Mask every secret at or below `key` in a value returned by `Config::get`
+fn mask_secrets(key: &str, value: &mut serde_json::Value) {
+    for secret in SECRET_KEYS {
+        let rest = if *secret == key {
+            ""
+        } else if let Some(rest) = secret.strip_prefix(key).and_then(|r| r.strip_prefix('.')) {
+            rest
+        } else {
+            continue;
+        };
+        let mut target = Some(&mut *value);
+        for part in rest.split('.').filter(|p| !p.is_empty()) {
+            target = target.and_then(|v| v.get_mut(part));
+        }
+        if let Some(target) = target {
+            if let Some(s) = target.as_str() {
+                *target = serde_json::Value::String(mask_secret(s));
+            }
+        }
+    }
+}
+
+/// Print one config value for scripts: strings bare, everything else as JSON.
+/// Secrets are masked unless `reveal` is set.
+pub fn get_config_value(config: &Config, key: &str, reveal: bool) -> Result<()> {
+    let mut value = config.get(key).ok_or_else(|| {
+        ContextHubError::Config(format!("Unknown config key '{}'", key))
+    })?;
+    if !reveal {
+        mask_secrets(key, &mut value);
+    }
+    match value {
+        serde_json::Value::String(s) => println!("{}", s),
+        other => println!("{}", other),
+    }
+    Ok(())
+}
+
+pub fn set_config_model(path: &Path, config: &mut Config, model: String) -> Result<()> {
     config.set_model(model.clone());
     config.save(path)?;
     println!("✓ Model set to: {}", model);
     Ok(())
 }
 
-pub fn set_config_ollama_url(path: &PathBuf, config: &mut Config, url: String) -> Result<()> {
+pub fn set_config_ollama_url(path: &Path, config: &mut Config, url: String) -> Result<()> {
     config.set_ollama_url(url.clone());
     config.save(path)?;
     println!("✓ Ollama URL set to: {}", url);
     Ok(())
 }
+
+pub fn set_config_range(path: &Path, config: &mut Config, range: usize) -> Result<()> {
+    if range == 0 {
+        return Err(ContextHubError::Config("Commit range must be at least 1".to_string()).into());
+    }
+    config.set_commit_range(range);
+    config.save(path)?;
+    println!("✓ Default commit range set to: {}", range);
+    Ok(())
+}
+
+pub fn set_config_ignore_whitespace(path: &Path, config: &mut Config, enabled: bool) -> Result<()> {
+    config.set_ignore_whitespace(enabled);
+    config.save(path)?;
+    println!(
+        "✓ Whitespace-only changes {}",
+        if enabled { "ignored in diffs" } else { "included in diffs" }
+    );
+    Ok(())
+}
+
+/// Set `context.project_overview` from `text` or the contents of `file`.
+/// Empty text removes the overview from exports.
+pub fn set_config_overview(
+    path: &Path,
+    config: &mut Config,
+    text: Option<String>,
+    file: Option<PathBuf>,
+) -> Result<()> {
+    let overview = match file {
+        Some(file) => std::fs::read_to_string(&file)
+            .with_context(|| format!("Could not read {}", file.display()))?,
+        None => text.unwrap_or_default(),
+    };
+    let overview = overview.trim().to_string();
+    let cleared = overview.is_empty();
+    let chars = overview.chars().count();
+    config.set_project_overview(overview);
+    config.save(path)?;
+    if cleared {
+        println!("✓ Project overview cleared");
+    } else {
+        println!("✓ Project overview set ({} chars); it appears at the top of the next export", chars);
+    }
+    Ok(())
+}
+
+/// Write the built-in extraction prompt to `.contexthub/prompt.md` as a
+/// starting point (an existing file is kept), then open it in the editor
+/// when run from a terminal
+pub fn edit_prompt(path: &Path) -> Result<()> {
+    let prompt_path = path.join(".contexthub/prompt.md");
+    if prompt_path.exists() {
+        println!("Prompt template: {}", prompt_path.display());
+    } else {
+        std::fs::write(&prompt_path, format!("{}\n", DEFAULT_PROMPT_TEMPLATE))
+            .with_context(|| format!("Could not write {}", prompt_path.display()))?;
+        println!("✓ Wrote the default prompt template to {}", prompt_path.display());
+    }
+
+    if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        open_editor(&prompt_path, "prompt template")?;
+        println!("✓ Saved; the next sync uses it (delete the file to go back to the built-in prompt)");
+    } else {
+        let placeholders: Vec<String> = PROMPT_PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect();
+        println!("  Placeholders: {}", placeholders.join(", "));
+        println!("  Delete the file to go back to the built-in prompt.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_get_masks_the_api_key_unless_revealed() {
+        assert_eq!(mask_secret("sk-0123456789abcd"), "sk-…abcd");
+        assert_eq!(mask_secret("short"), "…");
+
+        let mut config = Config::default();
+        config.ollama.api_key = Some("sk-0123456789abcd".to_string());
+
+        let mut key = config.get("ollama.api_key").unwrap();
+        mask_secrets("ollama.api_key", &mut key);
+        assert_eq!(key, "sk-…abcd");
+
+        let mut section = config.get("ollama").unwrap();
+        mask_secrets("ollama", &mut section);
+        assert_eq!(section["api_key"], "sk-…abcd");
+        assert_eq!(section["model"], config.ollama.model);
+    }
+}
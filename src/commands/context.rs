@@ -1,27 +1,93 @@
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
 
-use crate::core::context::ContextProcessor;
-use crate::utils::config::Config;
+use crate::commands::vacuum::format_size;
+use crate::core::context::{retain_branch, truncate_summary, write_export, ContextProcessor};
+use crate::core::storage::{GlobalContext, Storage};
+use crate::utils::config::global_db_path;
+use crate::utils::config::{resolve_limit, Config};
 
-pub fn display_context(path: &PathBuf, config: &Config) -> Result<()> {
-    let processor = ContextProcessor::new(path, config.clone())?;
-    let contexts = processor.get_global_context()?;
+/// Narrowing options for the `context` listing; all are optional and combine
+pub struct ContextFilter<'a> {
+    pub grep: Option<&'a str>,
+    pub author: Option<&'a str>,
+    pub tag: Option<&'a str>,
+    pub branch: Option<&'a str>,
+}
+
+pub fn display_context(
+    path: &PathBuf,
+    config: &Config,
+    filter: &ContextFilter<'_>,
+    oneline: bool,
+    limit: Option<usize>,
+) -> Result<()> {
+    let ContextFilter { grep, author, tag, branch } = *filter;
+    let limit = resolve_limit(limit, config.export.max_entries);
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
+    let mut contexts = match author {
+        Some(name) => processor.get_global_context_by_author(name)?,
+        None => processor.get_global_context()?,
+    };
+    if let Some(tag) = tag {
+        let tagged = processor.get_tagged_hashes(tag)?;
+        contexts.retain(|c| tagged.contains(&c.commit_hash));
+    }
+    if let Some(branch) = branch {
+        retain_branch(&mut contexts, branch);
+    }
 
     if contexts.is_empty() {
-        println!("No context stored. Run 'contexthub sync' first.");
+        match (author, tag, branch) {
+            (_, _, Some(branch)) => println!("No context entries from branch '{}'.", branch),
+            (_, Some(tag), None) => println!("No context entries tagged '{}'.", tag),
+            (Some(name), None, None) => println!("No context entries by an author matching '{}'.", name),
+            (None, None, None) => println!("No context stored. Run 'contexthub sync' first."),
+        }
+        return Ok(());
+    }
+
+    let pattern = grep
+        .map(|p| Regex::new(p).map_err(|e| anyhow::anyhow!("Invalid --grep pattern '{}': {}", p, e)))
+        .transpose()?;
+
+    if let Some(re) = &pattern {
+        let total = contexts.len();
+        contexts.retain(|c| re.is_match(&c.context_summary) || re.is_match(&c.commit_message));
+        if contexts.is_empty() {
+            println!("No context entries match '{}'.", re.as_str());
+            return Ok(());
+        }
+        if oneline {
+            print_oneline(&contexts, limit);
+            return Ok(());
+        }
+        println!("📚 Global Context ({} of {} entries match '{}')\n", contexts.len(), total, re.as_str());
+    } else if oneline {
+        print_oneline(&contexts, limit);
         return Ok(());
+    } else {
+        println!("📚 Global Context ({} entries)\n", contexts.len());
     }
 
-    println!("📚 Global Context ({} entries)\n", contexts.len());
+    let highlight = |text: &str| -> String {
+        match &pattern {
+            Some(re) => re.replace_all(text, "\x1b[1;33m$0\x1b[0m").into_owned(),
+            None => text.to_string(),
+        }
+    };
 
-    for ctx in contexts.iter().take(20) {
+    for ctx in contexts.iter().take(limit) {
         println!("┌─ {} ─", &ctx.commit_hash[..7.min(ctx.commit_hash.len())]);
         println!(
             "│ {}",
-            ctx.commit_message.lines().next().unwrap_or("No message")
+            highlight(ctx.commit_message.lines().next().unwrap_or("No message"))
+        );
+        println!(
+            "│ {}",
+            highlight(&truncate_summary(&ctx.context_summary, config.ui.display_summary_chars))
         );
-        println!("│ {}", ctx.context_summary);
         if !ctx.files_changed.is_empty() {
             let files: Vec<String> = serde_json::from_str(&ctx.files_changed).unwrap_or_default();
             println!("│ Files: {}", files.join(", "));
@@ -33,40 +99,553 @@ pub fn display_context(path: &PathBuf, config: &Config) -> Result<()> {
     Ok(())
 }
 
-pub fn export_context(path: &PathBuf, config: &Config, format: &str) -> Result<()> {
+/// The global store for reading, or `None` after telling the user how to
+/// create one
+pub fn open_global_store() -> Result<Option<Storage>> {
+    let store = Storage::open_global_readonly()?;
+    if store.is_none() {
+        println!(
+            "No global store yet at {}. Turn on context.mirror_to_global or run 'contexthub sync --mirror-global' in a repository.",
+            global_db_path()?.display()
+        );
+    }
+    Ok(store)
+}
+
+/// Entries mirrored into the global store from every repository, newest
+/// first, each labelled with the repository it came from
+pub fn display_global_context(
+    config: &Config,
+    grep: Option<&str>,
+    tag: Option<&str>,
+    oneline: bool,
+    limit: Option<usize>,
+) -> Result<()> {
+    let limit = resolve_limit(limit, config.export.max_entries);
+    let Some(store) = open_global_store()? else {
+        return Ok(());
+    };
+    let mut contexts = store.get_global_context()?;
+    if let Some(tag) = tag {
+        let tagged = store.get_tagged_hashes(&tag.trim_start_matches('#').to_lowercase())?;
+        contexts.retain(|c| tagged.contains(&c.commit_hash));
+    }
+    if let Some(p) = grep {
+        let re = Regex::new(p).map_err(|e| anyhow::anyhow!("Invalid --grep pattern '{}': {}", p, e))?;
+        contexts.retain(|c| re.is_match(&c.context_summary) || re.is_match(&c.commit_message));
+    }
+
+    if contexts.is_empty() {
+        println!("No matching entries in the global store.");
+        return Ok(());
+    }
+
+    let repos = store.get_repo_names()?;
+    let repo = |ctx: &GlobalContext| repos.get(&ctx.commit_hash).map(String::as_str).unwrap_or("?").to_string();
+
+    if oneline {
+        for ctx in contexts.iter().take(limit) {
+            let summary = ctx.context_summary.trim();
+            println!(
+                "{} {} [{}] {}",
+                &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+                ctx.commit_date.format("%Y-%m-%d"),
+                repo(ctx),
+                summary.lines().next().unwrap_or("")
+            );
+        }
+        return Ok(());
+    }
+
+    println!("🌐 Global Store ({} entries)\n", contexts.len());
+    for ctx in contexts.iter().take(limit) {
+        println!("┌─ {} · {} ─", repo(ctx), &ctx.commit_hash[..7.min(ctx.commit_hash.len())]);
+        println!("│ {}", ctx.commit_message.lines().next().unwrap_or("No message"));
+        println!("│ {}", truncate_summary(&ctx.context_summary, config.ui.display_summary_chars));
+        println!("└─ {} ─", ctx.commit_date.format("%Y-%m-%d %H:%M"));
+        println!();
+    }
+
+    Ok(())
+}
+
+/// git-log style `<shorthash> <date> <first sentence>`, undecorated for piping
+fn print_oneline(contexts: &[GlobalContext], limit: usize) {
+    for ctx in contexts.iter().take(limit) {
+        let summary = ctx.context_summary.trim();
+        let first_sentence = summary
+            .find(". ")
+            .map(|end| &summary[..=end])
+            .unwrap_or(summary)
+            .lines()
+            .next()
+            .unwrap_or("");
+        println!(
+            "{} {} {}",
+            &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+            ctx.commit_date.format("%Y-%m-%d"),
+            first_sentence
+        );
+    }
+}
+
+pub fn show_technologies(path: &PathBuf, config: &Config) -> Result<()> {
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
+    let counts = processor.technology_counts()?;
+
+    if counts.is_empty() {
+        println!("No technology information extracted yet.");
+        return Ok(());
+    }
+
+    let total = processor.get_global_context()?.len();
+    println!("🧰 Technologies ({} across {} entries)\n", counts.len(), total);
+
+    let width = counts.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, count) in &counts {
+        println!("  {:<width$}  {:>4}", name, count, width = width);
+    }
+
+    Ok(())
+}
+
+pub fn show_context(path: &PathBuf, config: &Config, hash: &str) -> Result<()> {
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
+    let ctx = processor
+        .get_context_by_hash(hash)?
+        .ok_or_else(|| anyhow::anyhow!("No context stored for commit '{}'", hash))?;
+
+    println!("Commit:   {}", ctx.commit_hash);
+    println!("Date:     {}", ctx.commit_date.format("%Y-%m-%d %H:%M"));
+    println!("Author:   {}", ctx.author.as_deref().unwrap_or("unknown"));
+    println!("Message:  {}", ctx.commit_message.lines().next().unwrap_or("No message"));
+    if let (Some(insertions), Some(deletions)) = (ctx.insertions, ctx.deletions) {
+        println!("Changes:  +{} -{} lines", insertions, deletions);
+    }
+    println!("Model:    {}", ctx.model.as_deref().unwrap_or("unknown"));
+    if ctx.manually_edited {
+        println!("Edited:   summary corrected by hand (kept on reindex)");
+    }
+    if ctx.extraction_failed {
+        println!("Status:   extraction failed (excluded from listings and exports)");
+    }
+    println!("Prompt:   {}", ctx.prompt_version.as_deref().map(|v| format!("v{}", v)).unwrap_or_else(|| "unknown".to_string()));
+    println!();
+    println!("{}", ctx.context_summary);
+
+    if let Some(extracted) = processor.get_extracted_context(&ctx.commit_hash)? {
+        println!();
+        println!("Impact: {}", extracted.impact);
+        for detail in &extracted.key_details {
+            println!("  - {}", detail);
+        }
+        if !extracted.technologies.is_empty() {
+            println!("Technologies: {}", extracted.technologies.join(", "));
+        }
+    }
+
+    let files: Vec<String> = serde_json::from_str(&ctx.files_changed).unwrap_or_default();
+    if !files.is_empty() {
+        println!();
+        println!("Files: {}", files.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Open an entry's summary in `$VISUAL`/`$EDITOR` and store the result as a
+/// manual correction. Lines starting with `#` are dropped, as in git.
+pub fn edit_context(path: &PathBuf, config: &Config, hash: &str) -> Result<()> {
     let processor = ContextProcessor::new(path, config.clone())?;
+    let ctx = processor
+        .get_context_by_hash(hash)?
+        .ok_or_else(|| anyhow::anyhow!("No context stored for commit '{}'", hash))?;
+    let short = &ctx.commit_hash[..7.min(ctx.commit_hash.len())];
 
-    let output = match format {
-        "markdown" | "md" => processor.export_context_markdown()?,
-        "json" => processor.export_context_json()?,
-        "claude" => {
-            let content = processor.export_for_claude()?;
-            let out_path = path.join("CLAUDE.md");
-            std::fs::write(&out_path, &content)?;
-            println!("✓ Exported to {}", out_path.display());
-            return Ok(());
+    let edit_path = path.join(".contexthub/SUMMARY_EDITMSG");
+    std::fs::write(
+        &edit_path,
+        format!(
+            "{}\n\n# Summary for {}: {}\n# Lines starting with '#' are ignored; an empty summary aborts the edit.\n",
+            ctx.context_summary.trim_end(),
+            short,
+            ctx.commit_message.lines().next().unwrap_or("No message"),
+        ),
+    )?;
+    let edited = open_editor(&edit_path, "summary").and_then(|_| Ok(std::fs::read_to_string(&edit_path)?));
+    let _ = std::fs::remove_file(&edit_path);
+
+    let summary = edited?
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    if summary.is_empty() {
+        anyhow::bail!("Empty summary; {} left unchanged", short);
+    }
+    if summary == ctx.context_summary.trim() {
+        println!("Summary for {} unchanged", short);
+        return Ok(());
+    }
+
+    processor.update_summary(&ctx.commit_hash, &summary)?;
+    println!("✓ Updated summary for {} (kept on reindex and re-sync)", short);
+    Ok(())
+}
+
+/// Delete the stored entry for one commit, e.g. to drop a bad summary
+pub fn remove_context(path: &PathBuf, config: &Config, commit: &str) -> Result<()> {
+    let processor = ContextProcessor::new(path, config.clone())?;
+    let ctx = processor
+        .get_context_by_hash(commit)?
+        .ok_or_else(|| anyhow::anyhow!("No context stored for commit '{}'", commit))?;
+    let short = &ctx.commit_hash[..7.min(ctx.commit_hash.len())];
+
+    let removed = processor.delete_context(&ctx.commit_hash)?;
+    println!(
+        "✓ Removed {} entr{} and {} TTL memory row{} for {} {}",
+        removed.entries,
+        if removed.entries == 1 { "y" } else { "ies" },
+        removed.ttl_memory,
+        if removed.ttl_memory == 1 { "" } else { "s" },
+        short,
+        ctx.commit_message.lines().next().unwrap_or("No message")
+    );
+    println!("  The next sync that covers this commit will analyze it again.");
+    Ok(())
+}
+
+/// Run the user's editor on `file` and wait for it to exit. `what` names
+/// the thing being edited in the error for a failed editor.
+pub fn open_editor(file: &Path, what: &str) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+
+    // Through the shell, so values like `code --wait` work
+    let status = if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .args(["/C", &format!("{} \"{}\"", editor, file.display())])
+            .status()
+    } else {
+        std::process::Command::new("sh")
+            .args(["-c", &format!("{} \"$1\"", editor), "sh"])
+            .arg(file)
+            .status()
+    }
+    .with_context(|| format!("Failed to start editor '{}'", editor))?;
+
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}; {} left unchanged", editor, status, what);
+    }
+    Ok(())
+}
+
+/// Export in `format`. markdown, json, ndjson and custom exporters without
+/// a configured file print to stdout; the assistant formats write their
+/// usual files. `output` sends any of them to that path instead (for
+/// obsidian, the directory the notes go into).
+pub fn export_context(
+    path: &PathBuf,
+    config: &Config,
+    format: &str,
+    branch: Option<String>,
+    append: bool,
+    limit: Option<usize>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let processor = ContextProcessor::open_readonly(path, config.clone())?.with_branch(branch);
+
+    let content = match format {
+        "markdown" | "md" => processor.export_context_markdown(limit)?,
+        "json" => processor.export_context_json(limit)?,
+        "ndjson" => {
+            if let Some(out_path) = output {
+                create_parent_dirs(&out_path)?;
+                let file = std::fs::File::create(&out_path)
+                    .with_context(|| format!("Failed to create {}", out_path.display()))?;
+                processor.export_context_ndjson(limit, &mut std::io::BufWriter::new(file))?;
+                report_export(&out_path, true, None);
+                return Ok(());
+            }
+            let stdout = std::io::stdout();
+            let result =
+                processor.export_context_ndjson(limit, &mut std::io::BufWriter::new(stdout.lock()));
+            // A consumer like `head` closing the pipe early is not an error
+            return match result {
+                Err(e) if is_broken_pipe(&e) => Ok(()),
+                other => other.map(|_| ()),
+            };
         }
-        "cursor" | "cursorrules" => {
-            let content = processor.export_for_cursor()?;
-            let out_path = path.join(".cursorrules");
-            std::fs::write(&out_path, &content)?;
-            println!("✓ Exported to {}", out_path.display());
-            return Ok(());
+        "claude" | "cursor" | "cursorrules" | "copilot" | "github-copilot" => {
+            let (format, content) = match format {
+                "claude" => ("claude", processor.export_for_claude(limit)?),
+                "cursor" | "cursorrules" => ("cursor", processor.export_for_cursor(limit)?),
+                _ => ("copilot", processor.export_for_copilot(limit)?),
+            };
+            let out_path = match output {
+                Some(out_path) => out_path,
+                None => assistant_export_path(path, format)?,
+            };
+            return write_export_file(&out_path, &content, append);
         }
-        "copilot" | "github-copilot" => {
-            let content = processor.export_for_copilot()?;
-            let dir = path.join(".github");
-            std::fs::create_dir_all(&dir)?;
-            let out_path = dir.join("copilot-instructions.md");
-            std::fs::write(&out_path, &content)?;
-            println!("✓ Exported to {}", out_path.display());
+        "obsidian" => {
+            if append {
+                anyhow::bail!("--append is not supported for the obsidian export");
+            }
+            let dir = output.unwrap_or_else(|| path.join(&config.export.obsidian_dir));
+            let count = processor.export_for_obsidian(&dir, limit)?;
+            println!("✓ Exported {} note(s) to {}", count, dir.display());
             return Ok(());
         }
-        _ => return Err(anyhow::anyhow!(
-            "Unsupported format: {}. Supported: markdown, json, claude, cursor, copilot", format
-        )),
+        _ => {
+            if let Some(name) = format.strip_prefix("custom:") {
+                return export_custom(path, config, &processor, name, append, limit, output);
+            }
+            return Err(anyhow::anyhow!(
+                "Unsupported format: {}. Supported: {}", format, config.export.format_names().join(", ")
+            ));
+        }
+    };
+
+    match output {
+        Some(out_path) if content.ends_with('\n') => write_export_file(&out_path, &content, append),
+        Some(out_path) => write_export_file(&out_path, &format!("{}\n", content), append),
+        None => {
+            println!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+/// `write_export` to a path that may not exist yet, creating its parent
+/// directories, and report the result
+fn write_export_file(out_path: &PathBuf, content: &str, append: bool) -> Result<()> {
+    create_parent_dirs(out_path)?;
+    let written = write_export(out_path, content, append)
+        .with_context(|| format!("Failed to write {}", out_path.display()))?;
+    report_export(out_path, written, None);
+    Ok(())
+}
+
+fn create_parent_dirs(out_path: &Path) -> Result<()> {
+    if let Some(parent) = out_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Run the `export.custom` command `name` with the entries as JSON on stdin;
+/// its stdout is written to `output`, the configured output file, or printed.
+fn export_custom(
+    path: &PathBuf,
+    config: &Config,
+    processor: &ContextProcessor,
+    name: &str,
+    append: bool,
+    limit: Option<usize>,
+    output_override: Option<PathBuf>,
+) -> Result<()> {
+    let Some(exporter) = config.export.custom.get(name) else {
+        let names: Vec<&str> = config.export.custom.keys().map(String::as_str).collect();
+        anyhow::bail!(
+            "No custom exporter named '{}' in export.custom (configured: {})",
+            name,
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        );
     };
+    let input = processor.export_for_custom(limit)?;
+    let output = run_custom_exporter(path, name, &exporter.command, &input)?;
 
-    println!("{}", output);
+    match output_override.or_else(|| exporter.output.as_ref().map(|file| path.join(file))) {
+        Some(out_path) => write_export_file(&out_path, &output, append)?,
+        None if append => anyhow::bail!("--append needs export.custom.{}.output to be set", name),
+        None => print!("{}", output),
+    }
     Ok(())
 }
+
+/// Run `command` through the shell in the repository root, feeding `input`
+/// on stdin. A failed spawn, non-zero exit or non-UTF-8 output is an error
+/// that includes the command's stderr.
+fn run_custom_exporter(path: &PathBuf, name: &str, command: &str, input: &str) -> Result<String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let mut child = cmd
+        .current_dir(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start custom exporter '{}' ({})", name, command))?;
+
+    // Feed stdin from another thread so a command that writes before it has
+    // read everything can't deadlock against us
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let result = child.wait_with_output()?;
+    let write_result = writer.join().expect("stdin writer panicked");
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    if !result.status.success() {
+        let detail = stderr.trim();
+        anyhow::bail!(
+            "Custom exporter '{}' failed ({}){}{}",
+            name,
+            result.status,
+            if detail.is_empty() { "" } else { ":\n" },
+            detail
+        );
+    }
+    // A command that exits successfully without reading all of its input
+    // (e.g. `echo`) closes the pipe early; that's its choice, not an error
+    if let Err(e) = write_result {
+        if e.kind() != std::io::ErrorKind::BrokenPipe {
+            anyhow::bail!("Failed to send entries to custom exporter '{}': {}", name, e);
+        }
+    }
+    if !stderr.trim().is_empty() {
+        log::warn!("Custom exporter '{}': {}", name, stderr.trim());
+    }
+    String::from_utf8(result.stdout)
+        .map_err(|_| anyhow::anyhow!("Custom exporter '{}' produced output that is not UTF-8", name))
+}
+
+/// Regenerate CLAUDE.md, .cursorrules and copilot-instructions.md in one
+/// pass, then every `export.custom` command that has an output file. The
+/// custom exporters share one JSON rendering of the entries.
+pub fn export_all(
+    path: &PathBuf,
+    config: &Config,
+    branch: Option<String>,
+    append: bool,
+    limit: Option<usize>,
+) -> Result<()> {
+    let processor = ContextProcessor::open_readonly(path, config.clone())?.with_branch(branch);
+
+    for (format, content) in processor.export_assistants(limit)? {
+        let out_path = assistant_export_path(path, format)?;
+        let written = write_export(&out_path, &content, append)?;
+        let size = std::fs::metadata(&out_path)?.len();
+        report_export(&out_path, written, Some(size));
+    }
+
+    if config.export.custom.is_empty() {
+        return Ok(());
+    }
+    let input = processor.export_for_custom(limit)?;
+    for (name, exporter) in &config.export.custom {
+        let Some(file) = &exporter.output else {
+            println!("  Skipped custom:{} (export.custom.{}.output is not set)", name, name);
+            continue;
+        };
+        let out_path = path.join(file);
+        let output = run_custom_exporter(path, name, &exporter.command, &input)?;
+        create_parent_dirs(&out_path)?;
+        let written = write_export(&out_path, &output, append)
+            .with_context(|| format!("Failed to write {}", out_path.display()))?;
+        let size = std::fs::metadata(&out_path)?.len();
+        report_export(&out_path, written, Some(size));
+    }
+    Ok(())
+}
+
+/// One line per export file; unchanged files are left alone, so say so
+/// rather than claim they were written
+fn report_export(out_path: &Path, written: bool, size: Option<u64>) {
+    let size = size.map(|s| format!(" ({})", format_size(s))).unwrap_or_default();
+    if written {
+        println!("✓ Exported to {}{}", out_path.display(), size);
+    } else {
+        let name = out_path.file_name().unwrap_or(out_path.as_os_str());
+        println!("✓ {} already up to date{}", name.to_string_lossy(), size);
+    }
+}
+
+/// Regenerate the `export.auto_export` files after a sync, rewriting only
+/// those whose content changed. Failures are reported, not returned, so they
+/// never fail the sync itself.
+pub fn auto_export(path: &PathBuf, config: &Config) {
+    if config.export.auto_export.is_empty() {
+        return;
+    }
+    let wanted: Vec<&str> = config
+        .export
+        .auto_export
+        .iter()
+        .map(|format| match format.as_str() {
+            "cursorrules" => "cursor",
+            "github-copilot" => "copilot",
+            other => other,
+        })
+        .collect();
+
+    let run = || -> Result<Vec<PathBuf>> {
+        let processor = ContextProcessor::open_readonly(path, config.clone())?;
+        let mut updated = Vec::new();
+        for (format, content) in processor.export_assistants(None)? {
+            if !wanted.contains(&format) {
+                continue;
+            }
+            let out_path = assistant_export_path(path, format)?;
+            if write_export(&out_path, &content, false)? {
+                updated.push(out_path);
+            }
+        }
+        Ok(updated)
+    };
+
+    match run() {
+        Ok(updated) if updated.is_empty() => println!("Auto-export: files already up to date"),
+        Ok(updated) => {
+            for out_path in updated {
+                println!("✓ Auto-exported {}", out_path.display());
+            }
+        }
+        Err(e) => {
+            println!("✗ Auto-export failed: {}", e);
+            log::error!("Auto-export failed: {}", e);
+        }
+    }
+}
+
+fn is_broken_pipe(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::BrokenPipe)
+            || cause
+                .downcast_ref::<serde_json::Error>()
+                .and_then(|json| json.io_error_kind())
+                == Some(std::io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Where an assistant export is written, creating `.github/` for Copilot
+fn assistant_export_path(path: &Path, format: &str) -> Result<PathBuf> {
+    Ok(match format {
+        "claude" => path.join("CLAUDE.md"),
+        "cursor" => path.join(".cursorrules"),
+        _ => {
+            let dir = path.join(".github");
+            std::fs::create_dir_all(&dir)?;
+            dir.join("copilot-instructions.md")
+        }
+    })
+}
@@ -1,79 +1,280 @@
 use anyhow::Result;
+use serde::Serialize;
 use std::path::PathBuf;
 
+use crate::commands::hook::{hook_status, HookStatus};
+use crate::core::git::GitAnalyzer;
 use crate::core::llm::check_ollama_installation;
+use crate::core::storage::{is_corruption_error, FingerprintStatus, Storage};
 use crate::utils::config::Config;
 
-pub fn doctor(path: &PathBuf, config: &Config) -> Result<()> {
-    println!("🔍 System Health Check\n");
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum DbStatus {
+    Ok,
+    Missing,
+    IntegrityFailed,
+    Corrupt,
+    Unreadable,
+}
 
-    // Git check
-    print!("  Git: ");
-    match crate::core::git::GitAnalyzer::new(path) {
-        Ok(git) => {
-            let commit_count = git.get_commit_count()?;
-            println!("✓ Repository found ({} commits)", commit_count);
-        }
-        Err(e) => println!("✗ Error: {}", e),
-    }
+/// Everything `doctor` checks. Serialized as-is for `doctor --json`.
+#[derive(Debug, Serialize)]
+struct DoctorReport {
+    git_repo: bool,
+    commit_count: Option<usize>,
+    git_error: Option<String>,
+    /// `ollama.provider`; with `openai` the Ollama binary isn't needed
+    provider: String,
+    ollama_installed: bool,
+    /// Whether the configured backend answered, whichever provider it is
+    ollama_running: bool,
+    endpoint: String,
+    initialized: bool,
+    initialized_items: usize,
+    db_present: bool,
+    db_status: DbStatus,
+    db_error: Option<String>,
+    /// Where `--fix` moved a corrupt database
+    db_backup: Option<String>,
+    /// Stored entries whose commit no longer exists in the repository;
+    /// absent when git or the database could not be checked
+    orphaned_entries: Option<usize>,
+    /// How many of those `--fix` removed
+    pruned_entries: Option<usize>,
+    /// Root commit the database was created for, compared with this repository's
+    fingerprint: Option<FingerprintStatus>,
+    /// Post-commit hook state; absent outside a git repository
+    hook: Option<HookStatus>,
+    recommendations: Vec<String>,
+}
+
+pub async fn doctor(path: &PathBuf, config: &Config, fix: bool, json: bool) -> Result<()> {
+    let report = check(path, config, fix).await?;
 
-    // Ollama installation
-    print!("  Ollama (installation): ");
-    if check_ollama_installation() {
-        println!("✓ Installed");
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        println!("✗ Not found - install from https://ollama.ai");
+        print_report(&report);
     }
+    Ok(())
+}
+
+async fn check(path: &PathBuf, config: &Config, fix: bool) -> Result<DoctorReport> {
+    let (commit_count, git_error) = match GitAnalyzer::new(path) {
+        Ok(git) => (Some(git.get_commit_count()?), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let hook = if git_error.is_none() { Some(hook_status(path)?) } else { None };
+
+    let ollama_installed = check_ollama_installation();
+    let llm = crate::core::llm::LlmProcessor::new(config.ollama.clone())?;
+    let ollama_running = llm.is_ollama_running().await;
 
-    // Ollama running
-    print!("  Ollama (running): ");
-    let llm = crate::core::llm::LlmProcessor::new(config.ollama.clone());
-    if llm.is_ollama_running() {
-        println!("✓ Running at {}", config.ollama.endpoint);
+    let context_dir = path.join(".contexthub");
+    let initialized = context_dir.exists();
+    let initialized_items = if initialized {
+        std::fs::read_dir(&context_dir)?.count()
     } else {
-        println!("✗ Not running - start with 'ollama serve'");
+        0
+    };
+
+    let db_path = context_dir.join("context.db");
+    let db_present = db_path.exists();
+    let mut db_error = None;
+    let mut db_status = if !db_present {
+        DbStatus::Missing
+    } else {
+        match Storage::new(&db_path).and_then(|s| s.integrity_check()) {
+            Ok(true) => DbStatus::Ok,
+            Ok(false) => DbStatus::IntegrityFailed,
+            Err(e) if is_corruption_error(&e) => DbStatus::Corrupt,
+            Err(e) => {
+                db_error = Some(e.to_string());
+                DbStatus::Unreadable
+            }
+        }
+    };
+
+    let db_corrupt = matches!(db_status, DbStatus::IntegrityFailed | DbStatus::Corrupt);
+    let mut db_backup = None;
+    if db_corrupt && fix {
+        let backup = Storage::backup_and_reset(&db_path)?;
+        db_backup = Some(backup.display().to_string());
+        db_status = DbStatus::Ok;
+    }
+
+    let mut orphaned_entries = None;
+    let mut pruned_entries = None;
+    let mut fingerprint = None;
+    if git_error.is_none() && db_present && matches!(db_status, DbStatus::Ok) {
+        let storage = Storage::new(&db_path)?;
+        fingerprint = Some(storage.check_fingerprint(GitAnalyzer::new(path)?.root_commit()?.as_deref())?);
+        let orphaned = orphaned_entries_in(path, &storage)?;
+        if fix && !orphaned.is_empty() {
+            pruned_entries = Some(storage.delete_commits(&orphaned)?.entries);
+            orphaned_entries = Some(0);
+        } else {
+            orphaned_entries = Some(orphaned.len());
+        }
     }
 
-    // ContextHub initialized
-    print!("  ContextHub initialized: ");
-    if path.join(".contexthub").exists() {
-        let count = std::fs::read_dir(path.join(".contexthub"))?.count();
-        println!("✓ Yes ({} items)", count);
+    let uses_ollama = config.ollama.provider == "ollama";
+    let mut recommendations = Vec::new();
+    if uses_ollama && !ollama_installed {
+        recommendations.push("Install Ollama: curl -fsSL https://ollama.ai/install.sh | sh".to_string());
+    }
+    if !ollama_running {
+        recommendations.push(if uses_ollama {
+            "Start Ollama: ollama serve".to_string()
+        } else {
+            "Check ollama.endpoint and the API key (CONTEXTHUB_API_KEY)".to_string()
+        });
+    }
+    if !initialized {
+        recommendations.push("Initialize: contexthub init".to_string());
+    }
+    if matches!(db_status, DbStatus::IntegrityFailed | DbStatus::Corrupt) {
+        recommendations.push("Back up and recreate the database: contexthub doctor --fix".to_string());
+    }
+    if matches!(fingerprint, Some(FingerprintStatus::Mismatch { .. })) {
+        recommendations.push(
+            "The database belongs to another repository: move .contexthub/context.db aside and run contexthub sync".to_string(),
+        );
+    }
+    if orphaned_entries.is_some_and(|n| n > 0) {
+        recommendations.push("Prune entries for commits no longer in the repository: contexthub doctor --fix".to_string());
+    }
+    if matches!(hook, Some(HookStatus::NotExecutable | HookStatus::BrokenBinary { .. })) {
+        recommendations.push("Repair the post-commit hook: contexthub hook repair".to_string());
+    }
+
+    Ok(DoctorReport {
+        git_repo: git_error.is_none(),
+        commit_count,
+        git_error,
+        provider: config.ollama.provider.clone(),
+        ollama_installed,
+        ollama_running,
+        endpoint: config.ollama.endpoint.clone(),
+        initialized,
+        initialized_items,
+        db_present,
+        db_status,
+        db_error,
+        db_backup,
+        orphaned_entries,
+        pruned_entries,
+        fingerprint,
+        hook,
+        recommendations,
+    })
+}
+
+/// Hashes of stored entries that no longer resolve to a commit in the
+/// repository at `path` (rewritten by a rebase, amend or force-push).
+pub fn orphaned_entries_in(path: &PathBuf, storage: &Storage) -> Result<Vec<String>> {
+    let git = GitAnalyzer::new(path)?;
+    Ok(git.missing_commits(&storage.all_hashes()?))
+}
+
+fn print_report(report: &DoctorReport) {
+    println!("🔍 System Health Check\n");
+
+    match (&report.commit_count, &report.git_error) {
+        (Some(count), _) => println!("  Git: ✓ Repository found ({} commits)", count),
+        (None, Some(e)) => println!("  Git: ✗ Error: {}", e),
+        (None, None) => println!("  Git: ✗ Error"),
+    }
+
+    if report.provider != "ollama" {
+        if report.ollama_running {
+            println!("  LLM API ({}): ✓ Reachable at {}", report.provider, report.endpoint);
+        } else {
+            println!("  LLM API ({}): ✗ No answer from {} - check the endpoint and API key", report.provider, report.endpoint);
+        }
     } else {
-        println!("✗ No - run 'contexthub init'");
+        if report.ollama_installed {
+            println!("  Ollama (installation): ✓ Installed");
+        } else {
+            println!("  Ollama (installation): ✗ Not found - install from https://ollama.ai");
+        }
+
+        if report.ollama_running {
+            println!("  Ollama (running): ✓ Running at {}", report.endpoint);
+        } else {
+            println!("  Ollama (running): ✗ Not running - start with 'ollama serve'");
+        }
     }
 
-    // Database
-    print!("  Database: ");
-    let db_path = path.join(".contexthub/context.db");
-    if db_path.exists() {
-        println!("✓ Exists");
+    if report.initialized {
+        println!("  ContextHub initialized: ✓ Yes ({} items)", report.initialized_items);
     } else {
-        println!("✗ Not found");
+        println!("  ContextHub initialized: ✗ No - run 'contexthub init'");
     }
 
-    println!();
-    println!("📝 Recommendations:");
-    let mut rec = 1;
+    if let Some(backup) = &report.db_backup {
+        println!("  Database: ✗ Corrupt");
+        println!("    → Moved corrupt database to {}", backup);
+        println!("    → Created a fresh database. Run 'contexthub sync' to rebuild context.");
+    } else {
+        match report.db_status {
+            DbStatus::Ok => println!("  Database: ✓ Exists (integrity ok)"),
+            DbStatus::Missing => println!("  Database: ✗ Not found"),
+            DbStatus::IntegrityFailed => println!("  Database: ✗ Integrity check failed"),
+            DbStatus::Corrupt => println!("  Database: ✗ Corrupt (cannot be opened)"),
+            DbStatus::Unreadable => println!(
+                "  Database: ✗ Cannot open: {}",
+                report.db_error.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
 
-    if !check_ollama_installation() {
-        println!("  {}. Install Ollama: curl -fsSL https://ollama.ai/install.sh | sh", rec);
-        rec += 1;
+    let short = |hash: &str| hash[..7.min(hash.len())].to_string();
+    match &report.fingerprint {
+        Some(FingerprintStatus::Matches { fingerprint }) => {
+            println!("  Fingerprint: ✓ Matches this repository (root commit {})", short(fingerprint))
+        }
+        Some(FingerprintStatus::Recorded { fingerprint }) => {
+            println!("  Fingerprint: ✓ Recorded (root commit {})", short(fingerprint))
+        }
+        Some(FingerprintStatus::Mismatch { stored, current }) => println!(
+            "  Fingerprint: ✗ Database was created for another repository (root commit {}, this one {})",
+            short(stored),
+            short(current)
+        ),
+        Some(FingerprintStatus::Unknown) => println!("  Fingerprint: - No commits yet"),
+        None => {}
     }
 
-    if !llm.is_ollama_running() {
-        println!("  {}. Start Ollama: ollama serve", rec);
-        rec += 1;
+    if let Some(pruned) = report.pruned_entries {
+        println!("  Entries: ✓ Pruned {} entries for missing commits", pruned);
+    } else {
+        match report.orphaned_entries {
+            Some(0) => println!("  Entries: ✓ All stored commits exist"),
+            Some(n) => println!("  Entries: ✗ {} entries point to commits no longer in the repository", n),
+            None => {}
+        }
     }
 
-    if !path.join(".contexthub").exists() {
-        println!("  {}. Initialize: contexthub init", rec);
-        rec += 1;
+    match &report.hook {
+        Some(HookStatus::Ok { binary }) => println!("  Hook: ✓ Installed (runs {})", binary),
+        Some(HookStatus::Missing) => println!("  Hook: - Not installed (optional: 'contexthub hook install')"),
+        Some(HookStatus::Foreign) => println!("  Hook: - post-commit hook is not ContextHub's"),
+        Some(HookStatus::NotExecutable) => println!("  Hook: ✗ Not executable"),
+        Some(HookStatus::BrokenBinary { binary }) => {
+            println!("  Hook: ✗ Binary not found: {}", binary)
+        }
+        None => {}
     }
 
-    if rec == 1 {
+    println!();
+    println!("📝 Recommendations:");
+    if report.recommendations.is_empty() {
         println!("  All good! No issues found.");
     }
-
-    Ok(())
+    for (i, rec) in report.recommendations.iter().enumerate() {
+        println!("  {}. {}", i + 1, rec);
+    }
 }
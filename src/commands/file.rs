@@ -0,0 +1,79 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::core::context::ContextProcessor;
+use crate::utils::config::Config;
+use crate::utils::error::ContextHubError;
+
+/// Stored context for one file or directory, oldest first, optionally with
+/// an LLM-written overview of how it evolved. Nothing is written to storage.
+pub async fn file_history(path: &PathBuf, config: &Config, file: &str, overview: bool) -> Result<()> {
+    let file = repo_relative(path, file);
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
+    let history = processor.file_history(&file)?;
+
+    if history.entries.is_empty() {
+        if history.unsynced > 0 {
+            println!(
+                "No stored context for {} yet; {} commit(s) touching it are not synced. Run 'contexthub sync'.",
+                file, history.unsynced
+            );
+        } else {
+            println!("No commits found touching {}.", file);
+        }
+        return Ok(());
+    }
+
+    println!("📄 {} ({} entries)\n", file, history.entries.len());
+    for ctx in &history.entries {
+        println!(
+            "{} {} {}",
+            ctx.commit_date.format("%Y-%m-%d"),
+            &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+            ctx.commit_message.lines().next().unwrap_or("")
+        );
+        println!("  {}", ctx.context_summary);
+    }
+    if history.unsynced > 0 {
+        println!();
+        println!(
+            "{} more commit(s) touching {} are not synced. Run 'contexthub sync' to include them.",
+            history.unsynced, file
+        );
+    }
+
+    if !overview {
+        return Ok(());
+    }
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    let context = processor.file_overview(&file, &history.entries).await?;
+    println!();
+    println!("📝 {}", context.summary);
+    if !context.key_details.is_empty() {
+        println!();
+        println!("Key details:");
+        for detail in &context.key_details {
+            println!("  - {}", detail);
+        }
+    }
+
+    Ok(())
+}
+
+/// `file` relative to the repository root, as git and stored entries name it
+fn repo_relative(repo_path: &Path, file: &str) -> String {
+    let file = Path::new(file);
+    let relative = if file.is_absolute() {
+        file.strip_prefix(repo_path).unwrap_or(file)
+    } else {
+        file
+    };
+    relative
+        .to_string_lossy()
+        .trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
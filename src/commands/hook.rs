@@ -1,54 +1,325 @@
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-pub fn install_hook(path: &PathBuf) -> Result<()> {
-    let git = crate::core::git::GitAnalyzer::new(path)?;
-    let hooks_dir = git.get_hooks_path();
+/// Git hooks ContextHub can install; the first is the default
+pub const HOOK_TYPES: &[&str] = &["post-commit", "pre-push"];
+
+/// Comment line that marks a hook file as ContextHub's, e.g.
+/// `# ContextHub post-commit hook`. Only files with it are replaced or removed.
+fn hook_marker(hook_type: &str) -> String {
+    format!("# ContextHub {} hook", hook_type)
+}
+
+fn is_ours(content: &str, hook_type: &str) -> bool {
+    let marker = hook_marker(hook_type);
+    content.lines().any(|line| line.trim() == marker)
+}
+
+/// Build the hook script, pinned to the binary that is installing it so the
+/// hook keeps working when `contexthub` isn't on the PATH git hooks see. The
+/// background sync's output goes to `.contexthub/logs/hook.log` (see
+/// `get_hook_log_path`), so a failing hook leaves a trace.
+fn hook_content(hook_type: &str, binary: &str) -> String {
+    let body = match hook_type {
+        "pre-push" => PRE_PUSH_BODY,
+        _ => POST_COMMIT_BODY,
+    };
+    format!(
+        "#!/bin/sh\n{}\nCONTEXTHUB_BIN={}\n{}",
+        hook_marker(hook_type),
+        shell_quote(binary),
+        body
+    )
+}
 
-    let hook_content = r#"#!/bin/sh
-# ContextHub post-commit hook
-# This hook automatically syncs context after each commit
+const POST_COMMIT_BODY: &str = r#"# This hook automatically syncs context after each commit
 
 # Check if we're in a ContextHub initialized repo
 if [ -d ".contexthub" ]; then
-    # Only sync last commit to avoid overwhelming the system
-    contexthub sync --last 1 &
+    mkdir -p .contexthub/logs
+    # Only sync last commit to avoid overwhelming the system. --detach hands
+    # the work to a background process (a shell `&` doesn't detach reliably
+    # under Git for Windows)
+    "$CONTEXTHUB_BIN" sync --last 1 --detach >> .contexthub/logs/hook.log 2>&1
 fi
 "#;
 
-    let hook_path = hooks_dir.join("post-commit");
-    std::fs::write(&hook_path, hook_content)?;
+/// Git writes `<local ref> <local sha> <remote ref> <remote sha>` on stdin for
+/// every ref being pushed. An all-zero sha means a deleted ref (local) or a
+/// new branch (remote); a new branch is synced from where it leaves the
+/// commits already on a remote. The push itself is never held up or failed.
+const PRE_PUSH_BODY: &str = r#"# This hook syncs context for the commits being pushed
+
+# Check if we're in a ContextHub initialized repo
+[ -d ".contexthub" ] || exit 0
+
+ranges=""
+while read -r local_ref local_sha remote_ref remote_sha; do
+    # Deleting a remote branch: nothing to sync
+    case "$local_sha" in *[!0]*) ;; *) continue ;; esac
+    case "$remote_sha" in
+        *[!0]*) ranges="$ranges $remote_sha..$local_sha" ;;
+        *)
+            base=$(git rev-list --boundary "$local_sha" --not --remotes | sed -n 's/^-//p' | head -n 1)
+            if [ -n "$base" ]; then
+                ranges="$ranges $base..$local_sha"
+            else
+                # Nothing pushed anywhere yet: the default sync range
+                ranges="$ranges HEAD"
+            fi
+            ;;
+    esac
+done
+
+mkdir -p .contexthub/logs
+for range in $ranges; do
+    if [ "$range" = HEAD ]; then
+        "$CONTEXTHUB_BIN" sync --detach
+    else
+        "$CONTEXTHUB_BIN" sync --from "$range" --detach
+    fi
+done >> .contexthub/logs/hook.log 2>&1
+exit 0
+"#;
+
+/// Path of the running executable, falling back to a PATH lookup.
+fn current_binary() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.canonicalize().ok())
+        .map(|p| sh_path(&p.display().to_string()))
+        .unwrap_or_else(|| "contexthub".to_string())
+}
+
+/// A path the `sh` running the hook understands. On Windows, canonicalize
+/// yields a `\\?\C:\...` verbatim path, which Git for Windows' sh can't
+/// execute; `C:/...` it can.
+fn sh_path(path: &str) -> String {
+    if cfg!(windows) {
+        path.trim_start_matches(r"\\?\").replace('\\', "/")
+    } else {
+        path.to_string()
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn shell_unquote(s: &str) -> String {
+    let s = s.trim();
+    match s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(inner) => inner.replace(r"'\''", "'"),
+        None => s.trim_matches('"').to_string(),
+    }
+}
 
+/// State of the ContextHub hook, as reported by `doctor`: the first
+/// installed one of `HOOK_TYPES`, or the post-commit hook if there is none.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HookStatus {
+    Missing,
+    /// A hook that ContextHub didn't write
+    Foreign,
+    /// Ours, but the hook file itself lacks the executable bit
+    NotExecutable,
+    /// Ours, but the binary it calls can't be found
+    BrokenBinary { binary: String },
+    Ok { binary: String },
+}
+
+fn hook_path(path: &PathBuf, hook_type: &str) -> Result<PathBuf> {
+    let git = crate::core::git::GitAnalyzer::new(path)?;
+    Ok(git.get_hooks_path().join(hook_type))
+}
+
+/// Binary the hook invokes. Hooks written before the path was pinned call
+/// plain `contexthub`.
+fn hook_binary(content: &str) -> String {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("CONTEXTHUB_BIN="))
+        .map(shell_unquote)
+        .unwrap_or_else(|| "contexthub".to_string())
+}
+
+fn is_executable(path: &Path) -> bool {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&hook_path)?.permissions();
+        std::fs::metadata(path)
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Resolve a binary the way `sh` would: as a path if it has a slash,
+/// otherwise through PATH.
+fn binary_resolves(binary: &str) -> bool {
+    if binary.contains('/') {
+        return is_executable(Path::new(binary));
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(binary))))
+        .unwrap_or(false)
+}
+
+pub fn hook_status(path: &PathBuf) -> Result<HookStatus> {
+    let mut statuses = Vec::new();
+    for hook_type in HOOK_TYPES {
+        statuses.push(single_hook_status(path, hook_type)?);
+    }
+    let installed = statuses
+        .iter()
+        .position(|s| !matches!(s, HookStatus::Missing | HookStatus::Foreign))
+        .unwrap_or(0);
+    Ok(statuses.swap_remove(installed))
+}
+
+fn single_hook_status(path: &PathBuf, hook_type: &str) -> Result<HookStatus> {
+    let hook_path = hook_path(path, hook_type)?;
+    if !hook_path.exists() {
+        return Ok(HookStatus::Missing);
+    }
+
+    let content = std::fs::read_to_string(&hook_path).unwrap_or_default();
+    if !is_ours(&content, hook_type) {
+        return Ok(HookStatus::Foreign);
+    }
+    if !is_executable(&hook_path) {
+        return Ok(HookStatus::NotExecutable);
+    }
+
+    let binary = hook_binary(&content);
+    if binary_resolves(&binary) {
+        Ok(HookStatus::Ok { binary })
+    } else {
+        Ok(HookStatus::BrokenBinary { binary })
+    }
+}
+
+fn write_hook(hook_path: &Path, hook_type: &str) -> Result<()> {
+    std::fs::write(hook_path, hook_content(hook_type, &current_binary()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(hook_path)?.permissions();
         perms.set_mode(0o755);
-        std::fs::set_permissions(&hook_path, perms)?;
+        std::fs::set_permissions(hook_path, perms)?;
+    }
+
+    if !is_executable(hook_path) {
+        bail!("Hook written but not executable: {}", hook_path.display());
     }
+    Ok(())
+}
+
+/// Install the `hook_type` hook (one of `HOOK_TYPES`). A ContextHub hook of
+/// the other type is left in place, so both can be active.
+pub fn install_hook(path: &PathBuf, hook_type: &str) -> Result<()> {
+    let hook_path = hook_path(path, hook_type)?;
+    let existing = std::fs::read_to_string(&hook_path).ok();
 
-    println!("✓ Git post-commit hook installed");
+    write_hook(&hook_path, hook_type)?;
+
+    match existing {
+        Some(content) if is_ours(&content, hook_type) => {
+            println!("✓ Git {} hook updated", hook_type);
+        }
+        _ => println!("✓ Git {} hook installed", hook_type),
+    }
     println!("  Path: {}", hook_path.display());
 
     Ok(())
 }
 
-pub fn uninstall_hook(path: &PathBuf) -> Result<()> {
-    let git = crate::core::git::GitAnalyzer::new(path)?;
-    let hooks_dir = git.get_hooks_path();
-    let hook_path = hooks_dir.join("post-commit");
+/// Rewrite every existing ContextHub hook so it points at the current binary.
+/// Foreign hooks are left alone.
+pub fn repair_hook(path: &PathBuf) -> Result<()> {
+    let mut repaired = 0;
+    let mut foreign = Vec::new();
+    for hook_type in HOOK_TYPES {
+        let hook_path = hook_path(path, hook_type)?;
+        match single_hook_status(path, hook_type)? {
+            HookStatus::Missing => continue,
+            HookStatus::Foreign => {
+                foreign.push(*hook_type);
+                continue;
+            }
+            _ => {}
+        }
+
+        write_hook(&hook_path, hook_type)?;
+        println!("✓ Git {} hook repaired", hook_type);
+        println!("  Path:   {}", hook_path.display());
+        println!("  Binary: {}", current_binary());
+        repaired += 1;
+    }
+
+    if repaired == 0 {
+        if foreign.is_empty() {
+            println!("No ContextHub hook found. Install it with: contexthub hook install");
+        } else {
+            println!(
+                "⚠️  {} hook exists but doesn't belong to ContextHub; leaving it untouched",
+                foreign.join(" and ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Print what `install_hook` would write, without touching the filesystem.
+pub fn preview_hook(path: &PathBuf, hook_type: &str) -> Result<()> {
+    let hook_path = hook_path(path, hook_type)?;
+
+    println!("Dry run — nothing will be written.");
+    println!("  Path: {}", hook_path.display());
 
     if hook_path.exists() {
+        let existing = std::fs::read_to_string(&hook_path).unwrap_or_default();
+        if is_ours(&existing, hook_type) {
+            println!("  Existing ContextHub hook will be replaced.");
+        } else {
+            println!("  ⚠️  An existing {} hook will be overwritten.", hook_type);
+        }
+    }
+
+    println!();
+    print!("{}", hook_content(hook_type, &current_binary()));
+
+    Ok(())
+}
+
+/// Remove whichever ContextHub hooks are installed. Returns the types removed.
+pub fn uninstall_hook(path: &PathBuf) -> Result<Vec<&'static str>> {
+    let mut removed = Vec::new();
+    for hook_type in HOOK_TYPES {
+        let hook_path = hook_path(path, hook_type)?;
+        if !hook_path.exists() {
+            continue;
+        }
         let content = std::fs::read_to_string(&hook_path)?;
-        if content.contains("ContextHub") {
+        if is_ours(&content, hook_type) {
             std::fs::remove_file(&hook_path)?;
-            println!("✓ Git post-commit hook removed");
+            println!("✓ Git {} hook removed", hook_type);
+            removed.push(*hook_type);
         } else {
-            println!("⚠️  Hook exists but doesn't belong to ContextHub");
+            println!("⚠️  {} hook exists but doesn't belong to ContextHub", hook_type);
         }
-    } else {
-        println!("No post-commit hook found");
     }
 
-    Ok(())
+    if removed.is_empty() {
+        println!("No ContextHub hook found");
+    }
+
+    Ok(removed)
 }
@@ -1,115 +1,175 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::commands::hook::{hook_status, HookStatus};
+use crate::commands::sync::estimate_sync_time;
 use crate::core::git::GitAnalyzer;
 use crate::core::llm;
 use crate::core::storage::Storage;
 use crate::utils::config::Config;
 
-pub async fn init_repo(path: &PathBuf) -> Result<()> {
+/// Answers given on the command line. Each one that is set skips its prompt;
+/// with `non_interactive` the remaining prompts take their defaults and the
+/// hook is installed and the initial sync run unless opted out.
+pub struct InitOptions {
+    pub non_interactive: bool,
+    pub model: Option<String>,
+    pub no_hook: bool,
+    pub no_sync: bool,
+}
+
+pub async fn init_repo(
+    path: &PathBuf,
+    endpoint_override: Option<&str>,
+    config_file: Option<&PathBuf>,
+    options: &InitOptions,
+) -> Result<()> {
+    // An existing `--config` file provides the defaults for the prompts below
+    let mut config = match config_file {
+        Some(file) if file.exists() => Config::load_from(file)?,
+        _ => Config {
+            file: config_file.cloned(),
+            ..Config::default()
+        },
+    };
+    let style = WizardStyle::new(&config);
+
     println!();
-    println!("  \x1b[1;36m╔═══════════════════════════════════════╗\x1b[0m");
-    println!("  \x1b[1;36m║\x1b[0m   🚀 \x1b[1mContextHub Setup Wizard\x1b[0m          \x1b[1;36m║\x1b[0m");
-    println!("  \x1b[1;36m╚═══════════════════════════════════════╝\x1b[0m");
+    style.banner("🚀", "ContextHub Setup Wizard");
     println!();
 
     // ── Step 1: Validate git repo ────────────────────────────
     print!("  Checking git repository... ");
     io::stdout().flush()?;
-    let _git = GitAnalyzer::new(path).map_err(|_| {
-        anyhow::anyhow!("Not a git repository. Run 'git init' first.")
-    })?;
-    println!("✓");
+    let git = GitAnalyzer::new(path).context("Not a git repository. Run 'git init' first.")?;
+    println!("{}", style.ok());
 
     let context_dir = path.join(".contexthub");
-    if context_dir.exists() {
+    // With `--config` the settings live outside `.contexthub/`, so there is
+    // nothing to repair: the directory alone means already initialized
+    let initialized = match config_file {
+        Some(_) => context_dir.exists(),
+        None => is_initialized(path),
+    };
+    if initialized {
         println!();
-        println!("  ⚠️  ContextHub already initialized in this directory.");
+        println!("  {} ContextHub already initialized in this directory.", style.warn());
         println!("  Run 'contexthub config show' to view current config.");
         return Ok(());
     }
+    if context_dir.exists() {
+        return repair_repo(path, endpoint_override, style);
+    }
 
     // ── Step 2: Create directory + DB ────────────────────────
     print!("  Creating .contexthub/ directory... ");
     io::stdout().flush()?;
-    std::fs::create_dir_all(&context_dir)?;
-    std::fs::create_dir_all(context_dir.join("cache"))?;
-    std::fs::create_dir_all(context_dir.join("logs"))?;
-    println!("✓");
+    create_layout(&context_dir)?;
+    println!("{}", style.ok());
 
     print!("  Initializing SQLite database... ");
     io::stdout().flush()?;
-    let _storage = Storage::new(&context_dir.join("context.db"))?;
-    println!("✓");
+    let storage = Storage::new(&context_dir.join("context.db"))?;
+    storage.check_fingerprint(git.root_commit()?.as_deref())?;
+    println!("{}", style.ok());
 
     print!("  Adding .contexthub/ to .gitignore... ");
     io::stdout().flush()?;
     add_to_gitignore(path)?;
-    println!("✓");
+    println!("{}", style.ok());
 
     // ── Step 3: Ollama model selection ───────────────────────
     println!();
-    println!("  \x1b[1m── Step 1/3: Ollama Configuration ──\x1b[0m");
+    style.step("Step 1/3: Ollama Configuration");
     println!();
 
-    let mut config = Config::default();
-
-    let endpoint = prompt_with_default(
-        "Ollama endpoint",
-        &config.ollama.endpoint,
-    )?;
-    config.ollama.endpoint = endpoint;
+    if let Some(url) = endpoint_override {
+        config.ollama.endpoint = url.to_string();
+    }
 
-    print!("  Checking Ollama... ");
-    io::stdout().flush()?;
+    if !options.non_interactive {
+        let endpoint = prompt_with_default(
+            "Ollama endpoint",
+            &config.ollama.endpoint,
+        )?;
+        config.ollama.endpoint = endpoint;
+    }
+    if let Some(model) = &options.model {
+        config.ollama.model = model.clone();
+    }
 
-    let ollama_running = reqwest::blocking::get(format!("{}/api/tags", config.ollama.endpoint))
-        .map(|r| r.status().is_success())
-        .unwrap_or(false);
+    let started = std::time::Instant::now();
+    let ollama_running = with_spinner(
+        style,
+        "  Checking Ollama... ",
+        llm::LlmProcessor::new(config.ollama.clone())?.is_ollama_running(),
+    )
+    .await?;
 
     if !ollama_running {
-        println!("✗ Not running");
+        if started.elapsed() >= llm::HEALTH_CHECK_TIMEOUT {
+            println!(
+                "{} Not running (timed out after {}s)",
+                style.fail(),
+                llm::HEALTH_CHECK_TIMEOUT.as_secs()
+            );
+        } else {
+            println!("{} Not running", style.fail());
+        }
         println!();
-        println!("  ⚠️  Ollama is not reachable at {}", config.ollama.endpoint);
+        println!("  {} Ollama is not reachable at {}", style.warn(), config.ollama.endpoint);
         println!("  You'll need to start it before syncing: ollama serve");
-        println!("  Using default model: {}", config.ollama.model);
+        println!("  Using model: {}", config.ollama.model);
         config.save(path)?;
-        print_final_summary(path, &config, false, false);
+        print_final_summary(path, &config, style, false, false);
         return Ok(());
     }
-    println!("✓ Running");
+    println!("{} Running", style.ok());
 
-    match llm::fetch_available_models(&config.ollama.endpoint) {
+    match llm::fetch_available_models(&config.ollama).await {
+        _ if options.model.is_some() => {
+            println!("  {} Model: {}", style.ok(), style.paint("1;32", &config.ollama.model));
+        }
+        Ok(models) if !models.is_empty() && options.non_interactive => {
+            config.ollama.model = models[llm::recommended_model(&models)].clone();
+            println!("  {} Model: {}", style.ok(), style.paint("1;32", &config.ollama.model));
+        }
         Ok(models) if !models.is_empty() => {
+            let recommended = llm::recommended_model(&models);
             println!();
             println!("  Available models:");
             println!();
             for (i, model) in models.iter().enumerate() {
-                println!("    \x1b[1;33m{}.\x1b[0m {}", i + 1, model);
+                let note = if i == recommended {
+                    format!(" {}", style.paint("2", "(recommended)"))
+                } else {
+                    String::new()
+                };
+                println!("    {} {}{}", style.paint("1;33", format!("{}.", i + 1)), model, note);
             }
             println!();
 
             let choice = prompt_with_default(
                 &format!("Select model [1-{}] or enter name", models.len()),
-                "1",
+                &(recommended + 1).to_string(),
             )?;
 
             let selected = if let Ok(idx) = choice.parse::<usize>() {
                 if idx >= 1 && idx <= models.len() {
                     models[idx - 1].clone()
                 } else {
-                    println!("  Invalid selection, using first model.");
-                    models[0].clone()
+                    println!("  Invalid selection, using the recommended model.");
+                    models[recommended].clone()
                 }
             } else if !choice.is_empty() {
                 choice
             } else {
-                models[0].clone()
+                models[recommended].clone()
             };
 
             config.ollama.model = selected;
-            println!("  ✓ Model: \x1b[1;32m{}\x1b[0m", config.ollama.model);
+            println!("  {} Model: {}", style.ok(), style.paint("1;32", &config.ollama.model));
         }
         Ok(_) => {
             println!("  No models found locally. Pull one with: ollama pull llama3.2");
@@ -123,25 +183,31 @@ pub async fn init_repo(path: &PathBuf) -> Result<()> {
 
     // ── Step 4: Auto-sync hook ───────────────────────────────
     println!();
-    println!("  \x1b[1m── Step 2/3: Git Hook (Auto-Sync) ──\x1b[0m");
+    style.step("Step 2/3: Git Hook (Auto-Sync)");
     println!();
     println!("  The post-commit hook will automatically extract context");
     println!("  after every git commit. This runs in the background but");
-    println!("  \x1b[1;33mmay add a brief delay\x1b[0m after each commit while Ollama processes.");
+    println!("  {} after each commit while Ollama processes.", style.paint("1;33", "may add a brief delay"));
     println!();
 
-    let install_hook = prompt_yes_no("  Enable auto-sync hook?", false)?;
+    let install_hook = if options.no_hook {
+        false
+    } else if options.non_interactive {
+        true
+    } else {
+        prompt_yes_no("  Enable auto-sync hook?", false)?
+    };
 
     let mut hook_installed = false;
     if install_hook {
-        match crate::commands::hook::install_hook(path) {
+        match crate::commands::hook::install_hook(path, "post-commit") {
             Ok(()) => {
                 config.git.hook_enabled = true;
                 config.git.auto_sync = true;
                 hook_installed = true;
             }
             Err(e) => {
-                println!("  ⚠️  Could not install hook: {}", e);
+                println!("  {} Could not install hook: {}", style.warn(), e);
                 println!("  You can install it later: contexthub hook install");
             }
         }
@@ -154,30 +220,36 @@ pub async fn init_repo(path: &PathBuf) -> Result<()> {
 
     // ── Step 5: Initial sync ─────────────────────────────────
     println!();
-    println!("  \x1b[1m── Step 3/3: Initial Sync ──\x1b[0m");
+    style.step("Step 3/3: Initial Sync");
     println!();
 
-    let git = GitAnalyzer::new(path)?;
     let commit_count = git.get_commit_count().unwrap_or(0);
     let sync_count = commit_count.min(config.context.default_commit_range);
 
     if commit_count == 0 {
         println!("  No commits in this repo yet. Sync will run after your first commit.");
     } else {
-        println!("  This repo has \x1b[1m{}\x1b[0m commit(s).", commit_count);
-        println!("  ContextHub will process the last \x1b[1m{}\x1b[0m commit(s).", sync_count);
+        println!("  This repo has {} commit(s).", style.bold(commit_count));
+        println!("  ContextHub will process the last {} commit(s).", style.bold(sync_count));
         println!();
-        println!("  \x1b[1;33m⚠ Note:\x1b[0m Each commit is sent to Ollama for analysis.");
-        println!("  This may take \x1b[1m~10-30 seconds per commit\x1b[0m depending on your hardware.");
+        println!(
+            "  {} Each commit is sent to Ollama for analysis.",
+            style.paint("1;33", style.pick("⚠ Note:", "Note:"))
+        );
+        println!("  This may take {} depending on your hardware.", style.bold("~10-30 seconds per commit"));
         if sync_count > 5 {
-            let est_min = sync_count * 10 / 60;
-            let est_max = sync_count * 30 / 60;
-            println!("  Estimated time: \x1b[1m{}-{} minutes\x1b[0m for {} commits.",
-                est_min.max(1), est_max.max(1), sync_count);
+            println!("  Estimated time: {} for {} commits.",
+                style.bold(estimate_sync_time(sync_count)), sync_count);
         }
         println!();
 
-        let do_sync = prompt_yes_no("  Run initial sync now?", true)?;
+        let do_sync = if options.no_sync {
+            false
+        } else if options.non_interactive {
+            true
+        } else {
+            prompt_yes_no("  Run initial sync now?", true)?
+        };
 
         if do_sync {
             println!();
@@ -186,12 +258,15 @@ pub async fn init_repo(path: &PathBuf) -> Result<()> {
                 path,
                 &config,
                 None,
+                None,
                 Some(sync_count),
+                &[],
+                1,
             ).await {
                 Ok(()) => {}
                 Err(e) => {
                     println!();
-                    println!("  ⚠️  Sync encountered an error: {}", e);
+                    println!("  {} Sync encountered an error: {}", style.warn(), e);
                     println!("  You can retry later: contexthub sync");
                 }
             }
@@ -200,23 +275,78 @@ pub async fn init_repo(path: &PathBuf) -> Result<()> {
         }
     }
 
-    print_final_summary(path, &config, hook_installed, commit_count > 0);
+    print_final_summary(path, &config, style, hook_installed, commit_count > 0);
     Ok(())
 }
 
-fn print_final_summary(_path: &PathBuf, config: &Config, hook_installed: bool, has_commits: bool) {
+/// `.contexthub/` exists but `config.json` is gone: recreate the missing
+/// pieces without touching the stored context, then write a default config.
+fn repair_repo(path: &PathBuf, endpoint_override: Option<&str>, style: WizardStyle) -> Result<()> {
+    let context_dir = path.join(".contexthub");
+    println!();
+    println!("  {} Found .contexthub/ without config.json; repairing.", style.warn());
+    println!();
+
+    print!("  Creating missing directories... ");
+    io::stdout().flush()?;
+    create_layout(&context_dir)?;
+    println!("{}", style.ok());
+
+    print!("  Opening SQLite database... ");
+    io::stdout().flush()?;
+    let storage = Storage::new(&context_dir.join("context.db"))?;
+    println!("{}", style.ok());
+    crate::commands::sync::check_fingerprint(path, &storage)?;
+
+    print!("  Writing default config.json... ");
+    io::stdout().flush()?;
+    let mut config = Config::default();
+    if let Some(url) = endpoint_override {
+        config.ollama.endpoint = url.to_string();
+    }
+    // Keep an installed hook working rather than reporting it as disabled
+    if matches!(hook_status(path)?, HookStatus::Ok { .. }) {
+        config.git.hook_enabled = true;
+        config.git.auto_sync = true;
+    }
+    config.save(path)?;
+    println!("{}", style.ok());
+
     println!();
-    println!("  \x1b[1;36m╔═══════════════════════════════════════╗\x1b[0m");
-    println!("  \x1b[1;36m║\x1b[0m   🎉 \x1b[1mContextHub Ready!\x1b[0m               \x1b[1;36m║\x1b[0m");
-    println!("  \x1b[1;36m╚═══════════════════════════════════════╝\x1b[0m");
+    println!("  Existing context was kept. Review the settings with: contexthub config show");
     println!();
-    println!("  \x1b[1mConfiguration:\x1b[0m");
+    Ok(())
+}
+
+/// Create `.contexthub/` and its subdirectories; existing ones are left as is.
+fn create_layout(context_dir: &std::path::Path) -> Result<()> {
+    std::fs::create_dir_all(context_dir)?;
+    std::fs::create_dir_all(context_dir.join("cache"))?;
+    std::fs::create_dir_all(context_dir.join("logs"))?;
+    Ok(())
+}
+
+fn print_final_summary(
+    _path: &PathBuf,
+    config: &Config,
+    style: WizardStyle,
+    hook_installed: bool,
+    has_commits: bool,
+) {
+    println!();
+    style.banner("🎉", "ContextHub Ready!");
+    println!();
+    println!("  {}", style.bold("Configuration:"));
     println!("    Model:     {}", config.ollama.model);
     println!("    Endpoint:  {}", config.ollama.endpoint);
-    println!("    Auto-sync: {}", if hook_installed { "✓ enabled" } else { "✗ disabled" });
+    if hook_installed {
+        println!("    Auto-sync: {} enabled", style.ok());
+    } else {
+        println!("    Auto-sync: {} disabled", style.fail());
+    }
     println!("    TTL:       {} days", config.context.ttl_days);
     println!();
-    println!("  \x1b[1mUseful commands:\x1b[0m");
+    println!("  {}", style.bold("Useful commands:"));
     if has_commits {
         println!("    contexthub sync            Sync more commits");
         println!("    contexthub context         View stored context");
@@ -234,8 +364,122 @@ fn print_final_summary(_path: &PathBuf, config: &Config, hook_installed: bool, h
     println!();
 }
 
+/// How the wizard decorates its output. Plain mode (`ui.plain_prompts` or
+/// `NO_COLOR`) is ASCII only: no escape codes, box drawing, emoji or spinner,
+/// for terminals and screen readers that render those poorly and for logs.
+#[derive(Clone, Copy)]
+struct WizardStyle {
+    plain: bool,
+}
+
+impl WizardStyle {
+    fn new(config: &Config) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self {
+            plain: config.ui.plain_prompts || no_color,
+        }
+    }
+
+    fn pick<'a>(&self, fancy: &'a str, plain: &'a str) -> &'a str {
+        if self.plain {
+            plain
+        } else {
+            fancy
+        }
+    }
+
+    fn ok(&self) -> &'static str {
+        self.pick("✓", "[OK]")
+    }
+
+    fn fail(&self) -> &'static str {
+        self.pick("✗", "[X]")
+    }
+
+    fn warn(&self) -> &'static str {
+        self.pick("⚠️ ", "[!]")
+    }
+
+    /// `text` wrapped in the SGR `code` (e.g. `1;32`), or as is when plain
+    fn paint(&self, code: &str, text: impl std::fmt::Display) -> String {
+        if self.plain {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        }
+    }
+
+    fn bold(&self, text: impl std::fmt::Display) -> String {
+        self.paint("1", text)
+    }
+
+    /// A boxed title line, or the title underlined with `=` when plain
+    fn banner(&self, icon: &str, title: &str) {
+        if self.plain {
+            println!("  {}", title);
+            println!("  {}", "=".repeat(title.len()));
+            return;
+        }
+        const WIDTH: usize = 39;
+        // Three spaces, the icon (two columns wide) and a space precede the title
+        let padding = WIDTH.saturating_sub(6 + title.chars().count());
+        println!("  \x1b[1;36m╔{}╗\x1b[0m", "═".repeat(WIDTH));
+        println!(
+            "  \x1b[1;36m║\x1b[0m   {} \x1b[1m{}\x1b[0m{}\x1b[1;36m║\x1b[0m",
+            icon,
+            title,
+            " ".repeat(padding)
+        );
+        println!("  \x1b[1;36m╚{}╝\x1b[0m", "═".repeat(WIDTH));
+    }
+
+    fn step(&self, title: &str) {
+        if self.plain {
+            println!("  -- {} --", title);
+        } else {
+            println!("  \x1b[1m── {} ──\x1b[0m", title);
+        }
+    }
+}
+
+/// Print `label` and await `fut`, animating a spinner after the label while
+/// waiting. Without a terminal (piped output) or in plain mode the label is
+/// printed once.
+async fn with_spinner<T>(
+    style: WizardStyle,
+    label: &str,
+    fut: impl std::future::Future<Output = T>,
+) -> Result<T> {
+    use std::io::IsTerminal;
+
+    print!("{}", label);
+    io::stdout().flush()?;
+    if style.plain || !io::stdout().is_terminal() {
+        return Ok(fut.await);
+    }
+
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    let mut ticker = tokio::time::interval(std::time::Duration::from_millis(100));
+    tokio::pin!(fut);
+    let mut frame = 0;
+    loop {
+        tokio::select! {
+            result = &mut fut => {
+                print!("\r{}", label);
+                io::stdout().flush()?;
+                return Ok(result);
+            }
+            _ = ticker.tick() => {
+                print!("\r{}{}", label, FRAMES[frame % FRAMES.len()]);
+                io::stdout().flush()?;
+                frame += 1;
+            }
+        }
+    }
+}
+
 /// Ask a yes/no question. Returns true for yes.
-fn prompt_yes_no(label: &str, default_yes: bool) -> Result<bool> {
+pub fn prompt_yes_no(label: &str, default_yes: bool) -> Result<bool> {
     let hint = if default_yes { "Y/n" } else { "y/N" };
     print!("{} [{}]: ", label, hint);
     io::stdout().flush()?;
@@ -267,12 +511,12 @@ fn prompt_with_default(label: &str, default: &str) -> Result<String> {
     }
 }
 
-pub fn is_initialized(path: &PathBuf) -> bool {
+pub fn is_initialized(path: &Path) -> bool {
     path.join(".contexthub/config.json").exists()
 }
 
 /// Ensures `.contexthub/` is in `.gitignore`. Creates the file if missing.
-fn add_to_gitignore(repo_path: &PathBuf) -> Result<()> {
+fn add_to_gitignore(repo_path: &Path) -> Result<()> {
     let gitignore_path = repo_path.join(".gitignore");
     let entry = ".contexthub/";
 
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::utils::logger::{get_hook_log_path, get_log_path};
+
+const DEFAULT_LINES: usize = 50;
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Print the tail of `.contexthub/logs/contexthub.log` (or, with `hook`, of
+/// the hooks' `hook.log`), optionally following it like `tail -f` (useful
+/// for watching background hook syncs).
+pub fn show_log(path: &Path, lines: Option<usize>, follow: bool, hook: bool) -> Result<()> {
+    let log_path = if hook { get_hook_log_path(path) } else { get_log_path(path) };
+    if !log_path.exists() {
+        println!("No log file yet at {}", log_path.display());
+        if !follow {
+            return Ok(());
+        }
+    }
+
+    let content = std::fs::read_to_string(&log_path).unwrap_or_default();
+    let all: Vec<&str> = content.lines().collect();
+    let n = lines.unwrap_or(DEFAULT_LINES);
+    for line in &all[all.len().saturating_sub(n)..] {
+        println!("{}", line);
+    }
+
+    if follow {
+        follow_file(&log_path, content.len() as u64)?;
+    }
+    Ok(())
+}
+
+fn follow_file(log_path: &PathBuf, mut pos: u64) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    loop {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+
+        let Ok(mut file) = std::fs::File::open(log_path) else {
+            continue;
+        };
+        let len = file.metadata()?.len();
+        if len < pos {
+            // Truncated or replaced (e.g. `uninstall` + `init`): start over
+            pos = 0;
+        }
+        if len == pos {
+            continue;
+        }
+
+        file.seek(SeekFrom::Start(pos))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        pos += buf.len() as u64;
+        stdout.write_all(&buf)?;
+        stdout.flush()?;
+    }
+}
@@ -1,10 +1,10 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::Path;
 
 use crate::utils::config::Config;
 
-pub fn display_ttl_memory(path: &PathBuf, _config: &Config) -> Result<()> {
-    let storage = crate::core::storage::Storage::new(&path.join(".contexthub/context.db"))?;
+pub fn display_ttl_memory(path: &Path, _config: &Config) -> Result<()> {
+    let storage = crate::core::storage::Storage::open_readonly(&path.join(".contexthub/context.db"))?;
 
     let memories = storage.get_ttl_memory()?;
 
@@ -26,7 +26,7 @@ pub fn display_ttl_memory(path: &PathBuf, _config: &Config) -> Result<()> {
     Ok(())
 }
 
-pub fn clear_ttl_memory(path: &PathBuf, _config: &Config) -> Result<()> {
+pub fn clear_ttl_memory(path: &Path, _config: &Config) -> Result<()> {
     let storage = crate::core::storage::Storage::new(&path.join(".contexthub/context.db"))?;
 
     storage.clear_ttl_memory()?;
@@ -35,7 +35,7 @@ pub fn clear_ttl_memory(path: &PathBuf, _config: &Config) -> Result<()> {
     Ok(())
 }
 
-pub fn set_ttl(path: &PathBuf, config: &mut Config, days: i32) -> Result<()> {
+pub fn set_ttl(path: &Path, config: &mut Config, days: i32) -> Result<()> {
     config.set_ttl_days(days);
     config.save(path)?;
     println!("✓ TTL set to {} days", days);
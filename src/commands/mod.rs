@@ -5,3 +5,11 @@ pub mod memory;
 pub mod config_cmd;
 pub mod doctor;
 pub mod hook;
+pub mod vacuum;
+pub mod summarize;
+pub mod uninstall;
+pub mod log_cmd;
+pub mod reindex;
+pub mod file;
+pub mod search;
+pub mod cache;
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use rayon::prelude::*;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::core::context::{ContextProcessor, RepromptJob};
+use crate::utils::config::Config;
+use crate::utils::error::ContextHubError;
+
+/// Re-run extraction on diffs stored with `context.store_diffs`, using the
+/// current model and prompt. Git is not read, so rebased-away commits work too.
+///
+/// With `jobs` > 1, entries are prepared on a thread pool of that size and up
+/// to `jobs` extraction requests are in flight at once. Results are written
+/// back one at a time through the processor's single connection.
+pub async fn reindex(path: &PathBuf, config: &Config, reprompt: bool, jobs: usize) -> Result<()> {
+    if !reprompt {
+        return Err(ContextHubError::Config(
+            "Nothing to reindex. Use --reprompt to re-run extraction on stored diffs.".to_string(),
+        )
+        .into());
+    }
+
+    let processor = ContextProcessor::new(path, config.clone())?;
+    let entries = processor.get_reprompt_entries()?;
+    let total = processor.get_context_count()?;
+
+    if entries.is_empty() {
+        println!("No stored diffs to re-prompt.");
+        if !config.context.store_diffs {
+            println!("Enable context.store_diffs and re-sync to keep diffs for future reindexing.");
+        }
+        return Ok(());
+    }
+
+    println!(
+        "Re-prompting {} of {} entr{} with {} (prompt v{})...",
+        entries.len(),
+        total,
+        if total == 1 { "y" } else { "ies" },
+        config.ollama.model,
+        processor.prompt_version()
+    );
+    let skipped = total - entries.len();
+    if skipped > 0 {
+        println!("  {} without a stored diff will be left as is", skipped);
+    }
+    println!();
+
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    let started = Instant::now();
+
+    // Diffs are read serially; rebuilding each entry from its diff is independent
+    let diffs = entries
+        .iter()
+        .map(|ctx| Ok((ctx, processor.stored_diff(&ctx.commit_hash)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    let prepared: Vec<RepromptJob> = pool.install(|| {
+        diffs
+            .into_par_iter()
+            .filter_map(|(ctx, diff)| diff.map(|diff| ContextProcessor::prepare_reprompt(ctx, diff)))
+            .collect()
+    });
+
+    let processor = &processor;
+    let mut results = stream::iter(prepared.iter())
+        .map(|job| async move { (job, processor.extract_reprompt(job).await) })
+        .buffer_unordered(jobs);
+
+    let mut done = 0;
+    let mut failed = 0;
+    while let Some((job, result)) = results.next().await {
+        done += 1;
+        let commit = &job.commit;
+        println!("[{}/{}] {} - {}", done, prepared.len(), commit.short_hash,
+            commit.message.lines().next().unwrap_or(""));
+
+        let stored = result.and_then(|context| {
+            processor.store_reprompt(job, &context)?;
+            Ok(context)
+        });
+        match stored {
+            Ok(context) => {
+                println!("  ✓ {}", context.summary);
+                log::info!("  ✓ reprompted {} - {}", commit.short_hash, context.summary);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("  ✗ Error: {}", e);
+                log::error!("  ✗ reprompt {} - {}", commit.short_hash, e);
+            }
+        }
+    }
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let rate = done as f64 / elapsed.max(0.001);
+    println!();
+    if failed > 0 {
+        println!(
+            "✓ Reindex complete in {:.1}s, {:.2} entries/s ({} failed, kept their previous summary)",
+            elapsed, rate, failed
+        );
+    } else {
+        println!("✓ Reindex complete in {:.1}s, {:.2} entries/s", elapsed, rate);
+    }
+
+    Ok(())
+}
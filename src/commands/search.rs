@@ -0,0 +1,132 @@
+use anyhow::Result;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use crate::commands::context::open_global_store;
+use crate::core::context::ContextProcessor;
+use crate::core::storage::{MATCH_END, MATCH_START};
+use crate::utils::config::{resolve_limit, Config};
+
+/// Print the entries matching `query`, best match first, each with a snippet
+/// of the text that matched
+pub fn search(path: &PathBuf, config: &Config, query: &str, limit: Option<usize>) -> Result<()> {
+    let limit = resolve_limit(limit, config.export.max_entries);
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
+    let hits = processor.search(query, limit)?;
+
+    if hits.is_empty() {
+        println!("No entries match \"{}\".", query);
+        return Ok(());
+    }
+
+    // Bold yellow on a terminal, `**` around matches anywhere else
+    let color = std::io::stdout().is_terminal()
+        && !config.ui.plain_prompts
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    let (start, end) = if color { ("\x1b[1;33m", "\x1b[0m") } else { ("**", "**") };
+
+    println!("🔎 {} match(es) for \"{}\"\n", hits.len(), query);
+    for hit in &hits {
+        let ctx = &hit.context;
+        println!(
+            "{} {} {}",
+            ctx.commit_date.format("%Y-%m-%d"),
+            &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+            ctx.commit_message.lines().next().unwrap_or("")
+        );
+        let snippet = hit
+            .snippet
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace(MATCH_START, start)
+            .replace(MATCH_END, end);
+        if !snippet.is_empty() {
+            println!("  {}", snippet);
+        }
+    }
+
+    Ok(())
+}
+
+/// Like `search`, over the entries mirrored into the global store, each
+/// labelled with the repository it came from
+pub fn search_global(config: &Config, query: &str, limit: Option<usize>) -> Result<()> {
+    let limit = resolve_limit(limit, config.export.max_entries);
+    let Some(store) = open_global_store()? else {
+        return Ok(());
+    };
+    let hits = store.search(query, limit)?;
+    if hits.is_empty() {
+        println!("No global entries match \"{}\".", query);
+        return Ok(());
+    }
+
+    let repos = store.get_repo_names()?;
+    println!("🔎 {} global match(es) for \"{}\"\n", hits.len(), query);
+    for hit in &hits {
+        let ctx = &hit.context;
+        println!(
+            "{} {} [{}] {}",
+            ctx.commit_date.format("%Y-%m-%d"),
+            &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+            repos.get(&ctx.commit_hash).map(String::as_str).unwrap_or("?"),
+            ctx.commit_message.lines().next().unwrap_or("")
+        );
+        let snippet = hit
+            .snippet
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace(MATCH_START, "**")
+            .replace(MATCH_END, "**");
+        if !snippet.is_empty() {
+            println!("  {}", snippet);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the entries closest in meaning to `query`, most similar first.
+/// Entries without an embedding are embedded first; if the embedding model
+/// stops answering, the search runs over the ones that have one.
+pub async fn semantic_search(path: &PathBuf, config: &Config, query: &str, limit: Option<usize>) -> Result<()> {
+    if config.ollama.embedding_model.is_empty() {
+        anyhow::bail!("Semantic search needs an embedding model. Set ollama.embedding_model, e.g. to \"nomic-embed-text\".");
+    }
+    let limit = resolve_limit(limit, config.export.max_entries);
+    let processor = ContextProcessor::new(path, config.clone())?;
+
+    let (embedded, missing) = processor.backfill_embeddings().await?;
+    if embedded > 0 {
+        eprintln!("Embedded {} entr(ies) with {}", embedded, config.ollama.embedding_model);
+    }
+    if missing > 0 {
+        eprintln!("⚠ {} entr(ies) have no embedding yet and are left out", missing);
+    }
+
+    let hits = processor.semantic_search(query, limit).await?;
+    if hits.is_empty() {
+        println!("No embedded entries to search.");
+        return Ok(());
+    }
+
+    println!("🔎 {} closest entr(ies) to \"{}\"\n", hits.len(), query);
+    for hit in &hits {
+        let ctx = &hit.context;
+        println!(
+            "{} {} {} ({:.2})",
+            ctx.commit_date.format("%Y-%m-%d"),
+            &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
+            ctx.commit_message.lines().next().unwrap_or(""),
+            hit.similarity
+        );
+        let summary = ctx.context_summary.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !summary.is_empty() {
+            println!("  {}", summary);
+        }
+    }
+
+    Ok(())
+}
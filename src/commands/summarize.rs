@@ -0,0 +1,46 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::core::context::ContextProcessor;
+use crate::utils::config::Config;
+use crate::utils::error::ContextHubError;
+
+/// Ad-hoc summary of a branch or squash range. Nothing is written to storage.
+/// Without `base`, the range starts at `GitAnalyzer::default_base`.
+pub async fn summarize(path: &PathBuf, config: &Config, base: Option<&str>, target: &str) -> Result<()> {
+    let processor = ContextProcessor::new(path, config.clone())?;
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => processor.git.default_base()?,
+    };
+    let base = base.as_str();
+
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    println!("Summarizing {}..{}", base, target);
+    println!();
+
+    let context = processor.summarize_range(base, target).await?;
+
+    println!("📝 {}", context.summary);
+    println!();
+    println!("Impact: {}", context.impact);
+    if !context.key_details.is_empty() {
+        println!();
+        println!("Key details:");
+        for detail in &context.key_details {
+            println!("  - {}", detail);
+        }
+    }
+    if !context.files_changed.is_empty() {
+        println!();
+        println!("Files: {}", context.files_changed.join(", "));
+    }
+    if !context.technologies.is_empty() {
+        println!("Technologies: {}", context.technologies.join(", "));
+    }
+
+    Ok(())
+}
@@ -1,20 +1,218 @@
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use futures::stream::{self, StreamExt};
+use regex::Regex;
 
+use crate::commands::init::prompt_yes_no;
 use crate::core::context::ContextProcessor;
-use crate::core::git::CommitInfo;
+use crate::core::git::{CommitInfo, GitAnalyzer};
+use crate::core::llm::{EmptyLlmResponse, ExtractedContext};
+use crate::core::storage::{FingerprintStatus, Storage};
 use crate::utils::config::Config;
+use crate::utils::error::ContextHubError;
+use crate::utils::logger::get_hook_log_path;
+
+/// Re-run the current `sync` command line without `--detach` as a process
+/// that outlives this one, with its output appended to the hook log. Used by
+/// the git hooks instead of a shell `&`, which Git for Windows doesn't
+/// reliably detach: on Unix the child gets its own process group, on
+/// Windows it is started as a detached process.
+pub fn spawn_detached(path: &Path) -> Result<()> {
+    let log_path = get_hook_log_path(path);
+    if let Some(dir) = log_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let log = std::fs::OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command
+        .args(std::env::args_os().skip(1).filter(|arg| arg != "--detach"))
+        .stdin(std::process::Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let child = command.spawn()?;
+    log::info!("Started background sync (pid {}), logging to {}", child.id(), log_path.display());
+    Ok(())
+}
+
+/// Copy this repository's high-impact entries into the global store when
+/// `context.mirror_to_global` is on. Like `auto_export`, a failure is
+/// reported but doesn't fail the sync that preceded it.
+pub fn mirror_to_global(path: &PathBuf, config: &Config) {
+    if !config.context.mirror_to_global {
+        return;
+    }
+    let run = || -> Result<usize> {
+        let processor = ContextProcessor::open_readonly(path, config.clone())?;
+        let workdir = processor.git.get_workdir().unwrap_or_else(|| path.clone());
+        let repo_name = workdir
+            .canonicalize()
+            .unwrap_or(workdir)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unnamed".to_string());
+        let global = Storage::open_global()?;
+        processor.mirror_high_impact(&global, &repo_name)
+    };
+    match run() {
+        Ok(0) => {}
+        Ok(n) => println!("✓ Mirrored {} high-impact entr(ies) to the global store", n),
+        Err(e) => {
+            log::warn!("Mirroring to the global store failed: {:#}", e);
+            eprintln!("⚠️  Could not mirror entries to the global store: {}", e);
+        }
+    }
+}
+
+/// Warn loudly when the database was created for a different repository,
+/// e.g. a `.contexthub/` copied along with a project template. Records the
+/// fingerprint on the first sync of a database that has none.
+pub fn check_fingerprint(path: &PathBuf, storage: &Storage) -> Result<()> {
+    let root = GitAnalyzer::new(path)?.root_commit()?;
+    if let FingerprintStatus::Mismatch { stored, current } = storage.check_fingerprint(root.as_deref())? {
+        eprintln!("⚠️  WARNING: .contexthub/context.db belongs to a different repository!");
+        eprintln!(
+            "   It was created for a repository with root commit {}, but this one starts at {}.",
+            &stored[..7.min(stored.len())],
+            &current[..7.min(current.len())]
+        );
+        eprintln!("   Stored entries and exports may describe unrelated commits.");
+        eprintln!("   Run 'contexthub doctor' for details.");
+        log::warn!("Repository fingerprint mismatch: stored {}, current {}", stored, current);
+    }
+    Ok(())
+}
+
+/// Check stored entries against the repository before syncing and offer to
+/// prune the ones whose commits were rewritten away.
+pub fn verify_stored(path: &PathBuf, storage: &Storage) -> Result<()> {
+    let orphaned = crate::commands::doctor::orphaned_entries_in(path, storage)?;
+    if orphaned.is_empty() {
+        println!("Verified stored entries: all commits exist");
+        return Ok(());
+    }
+
+    println!(
+        "{} stored entries point to commits no longer in the repository",
+        orphaned.len()
+    );
+    if !std::io::stdin().is_terminal() {
+        println!("Run 'contexthub doctor --fix' to prune them");
+        return Ok(());
+    }
+    if prompt_yes_no("  Prune them now?", true)? {
+        let deleted = storage.delete_commits(&orphaned)?;
+        println!("Pruned {} entries", deleted.entries);
+    }
+    Ok(())
+}
 
 pub async fn sync_context(
     path: &PathBuf,
     config: &Config,
     from_commit: Option<String>,
+    since: Option<DateTime<Utc>>,
     last_n: Option<usize>,
+    extra_skip_patterns: &[String],
+    jobs: usize,
 ) -> Result<()> {
     let processor = ContextProcessor::new(path, config.clone())?;
-    
+    let commits = select_new_commits(&processor, config, from_commit, since, last_n, extra_skip_patterns)?;
+    if commits.is_empty() {
+        return Ok(());
+    }
+
+    println!("Processing {} new commit(s)...", commits.len());
+    println!();
+
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    process_commits(&processor, config, &commits, jobs).await
+}
+
+/// List what `sync` would do without calling the LLM or writing anything:
+/// the commits it would process, a rough duration, and how many TTL
+/// entries have expired
+pub fn dry_run(
+    path: &PathBuf,
+    config: &Config,
+    from_commit: Option<String>,
+    since: Option<DateTime<Utc>>,
+    last_n: Option<usize>,
+    extra_skip_patterns: &[String],
+) -> Result<()> {
+    let processor = ContextProcessor::open_without_migrating(path, config.clone())?;
+    let expired = processor.count_expired_ttl()?;
+    if expired > 0 {
+        println!("Would clean up {} expired TTL entries", expired);
+    }
+
+    let commits = select_new_commits(&processor, config, from_commit, since, last_n, extra_skip_patterns)?;
+    if commits.is_empty() {
+        return Ok(());
+    }
+
+    println!("Would process {} new commit(s):", commits.len());
+    println!();
+    for commit in &commits {
+        println!("  {} {}", &commit.hash[..7.min(commit.hash.len())], commit.message.lines().next().unwrap_or(""));
+    }
+    println!();
+    println!(
+        "Estimated time: {} with {} (dry run, nothing was changed)",
+        estimate_sync_time(commits.len()),
+        config.ollama.model
+    );
+    Ok(())
+}
+
+/// Rough duration of an LLM run over `commits` commits, at ~10-30 seconds each
+pub fn estimate_sync_time(commits: usize) -> String {
+    let (low, high) = (commits * 10, commits * 30);
+    if high < 120 {
+        format!("{}-{} seconds", low, high)
+    } else {
+        format!("{}-{} minutes", (low / 60).max(1), high / 60)
+    }
+}
+
+/// The commits a sync would process, oldest first: the requested range
+/// without skip-pattern matches and already-stored commits. Says why when
+/// nothing is left.
+fn select_new_commits(
+    processor: &ContextProcessor,
+    config: &Config,
+    from_commit: Option<String>,
+    since: Option<DateTime<Utc>>,
+    last_n: Option<usize>,
+    extra_skip_patterns: &[String],
+) -> Result<Vec<CommitInfo>> {
+    let skip_patterns = compile_skip_patterns(
+        config.context.skip_message_patterns.iter().chain(extra_skip_patterns),
+    )?;
+
     let commits: Vec<CommitInfo> = if let Some(from) = from_commit {
-        processor.get_commit_range(&from, &processor.git.get_current_commit_hash()?)?
+        processor.get_revision_range(&from)?
+    } else if let Some(since) = since {
+        processor.git.get_commits_since(since)?
     } else if let Some(n) = last_n {
         processor.get_commits(n)?
     } else {
@@ -23,13 +221,22 @@ pub async fn sync_context(
 
     if commits.is_empty() {
         println!("No commits to process");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     // Process oldest-first so incremental context chaining builds forward
     let mut commits = commits;
     commits.reverse();
 
+    // Skip commits whose message matches a configured pattern (bots, merges, ...)
+    let total_before_filter = commits.len();
+    commits.retain(|c| !skip_patterns.iter().any(|re| re.is_match(&c.message)));
+    let pattern_skipped = total_before_filter - commits.len();
+
+    if pattern_skipped > 0 {
+        println!("Skipping {} commit(s) matching skip patterns", pattern_skipped);
+    }
+
     // Dedup: skip commits already stored
     let total_before_dedup = commits.len();
     commits.retain(|c| !processor.has_commit(&c.hash).unwrap_or(false));
@@ -40,62 +247,303 @@ pub async fn sync_context(
     }
 
     if commits.is_empty() {
-        println!("All commits already processed. Nothing to sync.");
+        println!("All commits already processed or skipped. Nothing to sync.");
+    }
+    Ok(commits)
+}
+
+/// Regenerate entries whose stored model or prompt version differs from the
+/// current config. Bypasses dedup since these commits are already stored.
+pub async fn refresh_stale(path: &PathBuf, config: &Config, jobs: usize) -> Result<()> {
+    let processor = ContextProcessor::new(path, config.clone())?;
+
+    let mut commits = processor.get_stale_commits()?;
+    if commits.is_empty() {
+        println!(
+            "All entries were generated with {} (prompt v{}). Nothing to refresh.",
+            config.ollama.model,
+            processor.prompt_version()
+        );
         return Ok(());
     }
+    commits.reverse();
 
-    println!("Processing {} new commit(s)...", commits.len());
+    println!(
+        "Refreshing {} stale entr{} with {} (prompt v{})...",
+        commits.len(),
+        if commits.len() == 1 { "y" } else { "ies" },
+        config.ollama.model,
+        processor.prompt_version()
+    );
+    println!();
+
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    process_commits(&processor, config, &commits, jobs).await
+}
+
+/// Re-process entries flagged as failed (e.g. after an Ollama outage).
+pub async fn retry_failed(path: &PathBuf, config: &Config) -> Result<()> {
+    let processor = ContextProcessor::new(path, config.clone())?;
+
+    let entries = processor.get_failed_entries()?;
+    if entries.is_empty() {
+        println!("No failed entries. Nothing to retry.");
+        return Ok(());
+    }
+
+    println!("Retrying {} failed entr{}...", entries.len(), if entries.len() == 1 { "y" } else { "ies" });
     println!();
 
-    if !processor.is_ollama_running() {
-        return Err(anyhow::anyhow!(
-            "Ollama is not running. Please start Ollama first:\n  ollama serve"
-        ));
+    if !processor.is_ollama_running().await {
+        return Err(ContextHubError::OllamaUnavailable.into());
+    }
+
+    let mut regenerated = 0;
+    for (idx, ctx) in entries.iter().enumerate() {
+        let short = &ctx.commit_hash[..7.min(ctx.commit_hash.len())];
+        println!("[{}/{}] {} - {}", idx + 1, entries.len(), short,
+            ctx.commit_message.lines().next().unwrap_or(""));
+
+        match processor.retry_failed(ctx).await {
+            Ok(context) => {
+                regenerated += 1;
+                println!("  ✓ {}", context.summary);
+                log::info!("  ✓ retried {} - {}", short, context.summary);
+            }
+            Err(e) => {
+                println!("  ✗ Error: {:#}", e);
+                log::error!("  ✗ retry {} - {:#}", short, e);
+            }
+        }
     }
 
-    for (idx, commit) in commits.iter().enumerate() {
-        println!("[{}/{}] {} - {}", idx + 1, commits.len(), &commit.short_hash,
-            commit.message.lines().next().unwrap_or(""));
-        log::info!("Processing commit {} ({}/{})", &commit.short_hash, idx + 1, commits.len());
-        
-        match processor.process_commit(commit).await {
+    println!();
+    println!(
+        "✓ Regenerated {} of {} previously failed entr{}",
+        regenerated,
+        entries.len(),
+        if entries.len() == 1 { "y" } else { "ies" }
+    );
+    log::info!("Retry complete: {}/{} regenerated", regenerated, entries.len());
+
+    Ok(())
+}
+
+/// Per-commit results of a sync, for the closing summary
+#[derive(Default)]
+struct SyncOutcome<'a> {
+    empty_responses: usize,
+    failures: Vec<(&'a CommitInfo, String)>,
+}
+
+impl<'a> SyncOutcome<'a> {
+    fn record(&mut self, commit: &'a CommitInfo, result: Result<ExtractedContext>) {
+        match result {
             Ok(context) => {
                 println!("  ✓ {}", context.summary);
                 log::info!("  ✓ {} - {}", &commit.short_hash, context.summary);
             }
             Err(e) => {
+                if e.is::<EmptyLlmResponse>() {
+                    self.empty_responses += 1;
+                }
                 println!("  ✗ Error: {}", e);
                 log::error!("  ✗ {} - {}", &commit.short_hash, e);
+                self.failures.push((commit, e.to_string()));
             }
         }
     }
+}
+
+/// Process `commits` oldest first. With `jobs` > 1, up to `jobs` commits are
+/// analyzed at once and reported as they finish; the caller turns chaining
+/// off for such runs, since each prompt would otherwise race the store of
+/// the commit before it.
+async fn process_commits(
+    processor: &ContextProcessor,
+    config: &Config,
+    commits: &[CommitInfo],
+    jobs: usize,
+) -> Result<()> {
+    let mut outcome = SyncOutcome::default();
+
+    if jobs > 1 {
+        let mut results = stream::iter(commits)
+            .map(|commit| async move { (commit, processor.process_commit(commit, None).await) })
+            .buffer_unordered(jobs);
+
+        let mut done = 0;
+        while let Some((commit, result)) = results.next().await {
+            done += 1;
+            println!("[{}/{}] {} - {}", done, commits.len(), &commit.short_hash,
+                commit.message.lines().next().unwrap_or(""));
+            outcome.record(commit, result);
+        }
+    } else {
+        let depth = config.context.diff_prefetch;
+        let mut prefetched = if depth > 0 && commits.len() > 1 {
+            Some(spawn_diff_prefetch(processor.git.reopen()?, commits, depth))
+        } else {
+            None
+        };
+
+        for (idx, commit) in commits.iter().enumerate() {
+            println!("[{}/{}] {} - {}", idx + 1, commits.len(), &commit.short_hash,
+                commit.message.lines().next().unwrap_or(""));
+            log::info!("Processing commit {} ({}/{})", &commit.short_hash, idx + 1, commits.len());
+
+            // A failed prefetch falls back to an inline read, which reports the error
+            let diff = match prefetched.as_mut() {
+                Some(rx) => rx.recv().await.and_then(|d| d.ok()),
+                None => None,
+            };
+
+            outcome.record(commit, processor.process_commit(commit, diff).await);
+        }
+    }
+    let SyncOutcome { empty_responses, failures } = outcome;
 
     println!();
     let count = processor.get_context_count()?;
     println!("✓ Sync complete. Total context entries: {}", count);
     log::info!("Sync complete. Total entries: {}", count);
+    if empty_responses > 0 {
+        println!(
+            "  {} commit(s) got an empty response from Ollama and were marked as failed",
+            empty_responses
+        );
+    }
+
+    if !failures.is_empty() {
+        println!();
+        println!("Failed commits ({}):", failures.len());
+        for (commit, error) in &failures {
+            // Multi-line errors (e.g. with hints) are cut to their first line
+            let error = error.lines().next().unwrap_or("");
+            println!("  {} — {}", commit.short_hash, error);
+            log::error!("Failed: {} - {}", commit.short_hash, error);
+        }
+        return Err(ContextHubError::SyncFailures(failures.len()).into());
+    }
 
     Ok(())
 }
 
-pub fn get_sync_status(path: &PathBuf, config: &Config) -> Result<()> {
-    let processor = ContextProcessor::new(path, config.clone())?;
+/// Read diffs for `commits`, in order, on a background thread so git IO
+/// overlaps with LLM calls. At most `depth` diffs are buffered ahead.
+fn spawn_diff_prefetch(
+    git: GitAnalyzer,
+    commits: &[CommitInfo],
+    depth: usize,
+) -> tokio::sync::mpsc::Receiver<Result<String>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(depth);
+    let hashes: Vec<String> = commits.iter().map(|c| c.hash.clone()).collect();
+
+    std::thread::spawn(move || {
+        for hash in hashes {
+            // Receiver gone: sync finished or bailed out
+            if tx.blocking_send(git.get_diff(&hash)).is_err() {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// Parse a `--since` value: an RFC 3339 timestamp, a `YYYY-MM-DD` date
+/// (local midnight), or a relative span such as "2 weeks ago" or "3d".
+pub fn parse_since(input: &str) -> Result<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        if let Some(dt) = date.and_hms_opt(0, 0, 0).and_then(|d| d.and_local_timezone(Local).single()) {
+            return Ok(dt.with_timezone(&Utc));
+        }
+    }
+
+    let span: jiff::Span = input.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid --since value '{}'. Use a date (2024-01-15) or a relative span (\"2 weeks ago\")",
+            input
+        )
+    })?;
+    // "2 weeks" and "2 weeks ago" both mean the past
+    let span = if span.signum() > 0 { span.negate() } else { span };
+    let then = jiff::Zoned::now().checked_add(span)?;
+
+    DateTime::from_timestamp(then.timestamp().as_second(), 0)
+        .ok_or_else(|| anyhow::anyhow!("--since value '{}' is out of range", input))
+}
+
+fn compile_skip_patterns<'a>(patterns: impl Iterator<Item = &'a String>) -> Result<Vec<Regex>> {
+    patterns
+        .map(|p| {
+            Regex::new(p).map_err(|e| anyhow::anyhow!("Invalid skip pattern '{}': {}", p, e))
+        })
+        .collect()
+}
+
+pub async fn get_sync_status(path: &PathBuf, config: &Config, porcelain: bool) -> Result<()> {
+    let processor = ContextProcessor::open_readonly(path, config.clone())?;
     
     let total_commits = processor.git.get_commit_count()?;
     let stored_count = processor.get_context_count()?;
-    let last_processed = processor.get_last_commit()?;
+    let failed_count = processor.get_failed_count()?;
+    let last_processed = processor
+        .get_last_commit()?
+        .map(|last| last[..7.min(last.len())].to_string());
+    let ollama_running = processor.is_ollama_running().await;
+
+    // Porcelain keys and values are a stable contract; don't reformat them.
+    if porcelain {
+        println!("total-commits {}", total_commits);
+        println!("stored {}", stored_count);
+        println!("failed {}", failed_count);
+        println!("last-processed {}", last_processed.as_deref().unwrap_or("none"));
+        println!("ollama {}", if ollama_running { "running" } else { "stopped" });
+        return Ok(());
+    }
     
     println!("Sync Status:");
     println!("  Total commits in repo: {}", total_commits);
     println!("  Stored context entries: {}", stored_count);
+    if failed_count > 0 {
+        println!("  Failed extractions: {}", failed_count);
+    }
+    let tag_counts = processor.get_tag_counts()?;
+    if !tag_counts.is_empty() {
+        let tags: Vec<String> = tag_counts
+            .iter()
+            .map(|(tag, count)| format!("{} ({})", tag, count))
+            .collect();
+        println!("  Tags: {}", tags.join(", "));
+    }
+    let (insertions, deletions, with_stats) = processor.get_churn_totals()?;
+    if with_stats > 0 {
+        println!(
+            "  Line churn: +{} -{} across {} entr{}",
+            insertions,
+            deletions,
+            with_stats,
+            if with_stats == 1 { "y" } else { "ies" }
+        );
+    }
     
     if let Some(last) = last_processed {
-        println!("  Last processed: {}", &last[..7.min(last.len())]);
+        println!("  Last processed: {}", last);
     } else {
         println!("  Last processed: None");
     }
 
-    if processor.is_ollama_running() {
+    if ollama_running {
         println!("  Ollama: ✓ Running");
     } else {
         println!("  Ollama: ✗ Not running");
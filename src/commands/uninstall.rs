@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::commands::init::prompt_yes_no;
+
+pub fn uninstall(path: &PathBuf, keep_data: bool, yes: bool) -> Result<()> {
+    let context_dir = path.join(".contexthub");
+
+    if !keep_data && context_dir.exists() && !yes {
+        let confirmed = prompt_yes_no(
+            &format!("Delete {} and all stored context?", context_dir.display()),
+            false,
+        )?;
+        if !confirmed {
+            println!("Aborted. Nothing was removed.");
+            return Ok(());
+        }
+    }
+
+    let mut removed = Vec::new();
+
+    // Reuse the hook uninstaller; it leaves foreign hooks alone
+    for hook_type in crate::commands::hook::uninstall_hook(path)? {
+        removed.push(format!("{} hook", hook_type));
+    }
+
+    if keep_data {
+        let config_path = context_dir.join("config.json");
+        if config_path.exists() {
+            std::fs::remove_file(&config_path)?;
+            removed.push(config_path.display().to_string());
+        }
+    } else if context_dir.exists() {
+        std::fs::remove_dir_all(&context_dir)?;
+        removed.push(context_dir.display().to_string());
+    }
+
+    if remove_from_gitignore(path)? {
+        removed.push(".contexthub/ entry in .gitignore".to_string());
+    }
+
+    println!();
+    if removed.is_empty() {
+        println!("Nothing to remove — ContextHub is not installed here.");
+    } else {
+        println!("✓ ContextHub uninstalled. Removed:");
+        for item in &removed {
+            println!("  - {}", item);
+        }
+        if keep_data {
+            println!("  Database kept at {}", context_dir.join("context.db").display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the `.contexthub/` entry (and the comment `init` writes above it).
+/// Returns whether anything was removed.
+fn remove_from_gitignore(repo_path: &Path) -> Result<bool> {
+    let gitignore_path = repo_path.join(".gitignore");
+    if !gitignore_path.exists() {
+        return Ok(false);
+    }
+
+    let content = std::fs::read_to_string(&gitignore_path)?;
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            line != ".contexthub/" && line != "# ContextHub local data"
+        })
+        .collect();
+
+    if kept.len() == content.lines().count() {
+        return Ok(false);
+    }
+
+    let mut new_content = kept.join("\n").trim_end().to_string();
+    if !new_content.is_empty() {
+        new_content.push('\n');
+    }
+    std::fs::write(&gitignore_path, new_content)?;
+    Ok(true)
+}
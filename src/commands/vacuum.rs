@@ -0,0 +1,42 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::core::storage::Storage;
+
+pub fn vacuum(path: &Path) -> Result<()> {
+    let db_path = path.join(".contexthub/context.db");
+    let storage = Storage::new(&db_path)?;
+
+    let before = db_size(&db_path);
+    storage.vacuum()?;
+    let after = db_size(&db_path);
+
+    println!("✓ Database vacuumed");
+    println!("  Before: {}", format_size(before));
+    println!("  After:  {}", format_size(after));
+    if before > after {
+        println!("  Reclaimed: {}", format_size(before - after));
+    }
+
+    Ok(())
+}
+
+/// Size of the database on disk, including its WAL file.
+fn db_size(db_path: &PathBuf) -> u64 {
+    let wal_path = db_path.with_extension("db-wal");
+    [db_path, &wal_path]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,78 @@
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::core::llm::ExtractedContext;
+
+/// On-disk cache of LLM extraction results, one `<key>.json` file per
+/// prompt in `.contexthub/cache/`. Lets a re-sync after clearing the
+/// database reuse earlier answers instead of calling the model again.
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// SHA-256 (hex) over `parts`, NUL-separated so that moving text from one
+    /// part to the next changes the key
+    pub fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The cached result for `key`. A missing, unreadable or corrupt file is
+    /// a miss, never an error.
+    pub fn get(&self, key: &str) -> Option<ExtractedContext> {
+        let path = self.path(key);
+        let text = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&text) {
+            Ok(context) => Some(context),
+            Err(e) => {
+                log::warn!("Ignoring unreadable cache file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Store `context` under `key`. Written to a temporary file and renamed
+    /// into place, so an interrupted write never leaves a partial entry.
+    pub fn put(&self, key: &str, context: &ExtractedContext) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(context)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Delete every cached result. Returns the number of entries removed.
+    pub fn clear(&self) -> anyhow::Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.ends_with(".json") || name.ends_with(".json.tmp") {
+                std::fs::remove_file(&path)?;
+                if name.ends_with(".json") {
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+}
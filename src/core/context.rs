@@ -1,28 +1,134 @@
-use std::path::PathBuf;
+use anyhow::Context;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::core::cache::ResponseCache;
 use crate::core::git::{CommitInfo, GitAnalyzer};
-use crate::core::llm::{ExtractedContext, LlmProcessor};
-use crate::core::storage::{Storage, GlobalContext};
-use crate::utils::config::Config;
+use crate::core::llm::{
+    load_prompt_guidance, load_prompt_template, EmptyLlmResponse, ExtractedContext, LlmProcessor,
+};
+use crate::core::storage::{
+    DeletedRows, EntryOrigin, GlobalContext, SearchHit, SemanticHit, Storage, REPO_TAG_PREFIX,
+};
+use crate::utils::config::{resolve_limit, Config, EXPORT_SECTIONS, IMPACT_LEVELS};
+
+/// Floor for the diff share of a chunked prompt, so a large previous-context
+/// or guidance section can't shrink chunks to nothing.
+const MIN_CHUNK_TOKENS: usize = 256;
+
+/// Line that stands in for the content of a binary file in a diff
+pub const BINARY_FILE_NOTE: &str = "Binary file changed: ";
+
+/// Export blocks that read the same whatever the target format
+struct SharedSections {
+    overview: Option<String>,
+    files: Option<String>,
+    technologies: Option<String>,
+}
+
+/// Stored context for one file, see `ContextProcessor::file_history`
+pub struct FileHistory {
+    /// Oldest first
+    pub entries: Vec<GlobalContext>,
+    /// Commits from git history that touched the file but have no entry
+    pub unsynced: usize,
+}
+
+/// A stored entry rebuilt for `reindex --reprompt`
+pub struct RepromptJob {
+    pub commit: CommitInfo,
+    diff: String,
+    files: Vec<String>,
+}
 
 pub struct ContextProcessor {
     pub git: GitAnalyzer,
     llm: LlmProcessor,
     storage: Storage,
     config: Config,
+    /// `.contexthub/notes.md`, emitted as the `notes` export section
+    notes: Option<String>,
+    /// Explicit `--branch` for exports; see `export_branch`
+    branch: Option<String>,
+    /// Set after an embedding request fails, so the rest of the run stops
+    /// trying; `search --semantic` backfills what was missed
+    embeddings_unavailable: AtomicBool,
 }
 
 impl ContextProcessor {
     pub fn new(repo_path: &PathBuf, config: Config) -> anyhow::Result<Self> {
-        let git = GitAnalyzer::new(repo_path)?;
         let storage = Storage::new(&repo_path.join(".contexthub/context.db"))?;
-        let llm = LlmProcessor::new(config.ollama.clone());
+        Self::with_storage(repo_path, config, storage)
+    }
+
+    /// For commands that only read stored context (see `Storage::open_readonly`)
+    pub fn open_readonly(repo_path: &PathBuf, config: Config) -> anyhow::Result<Self> {
+        let storage = Storage::open_readonly(&repo_path.join(".contexthub/context.db"))?;
+        Self::with_storage(repo_path, config, storage)
+    }
+
+    /// For commands that must not write at all (see `Storage::open_without_migrating`)
+    pub fn open_without_migrating(repo_path: &PathBuf, config: Config) -> anyhow::Result<Self> {
+        let storage = Storage::open_without_migrating(&repo_path.join(".contexthub/context.db"))?;
+        Self::with_storage(repo_path, config, storage)
+    }
+
+    fn with_storage(repo_path: &PathBuf, config: Config, storage: Storage) -> anyhow::Result<Self> {
+        let git = GitAnalyzer::with_config(repo_path, config.git.clone())?
+            .with_ignore_globs(&config.context.ignore_globs)?;
+        let guidance = load_prompt_guidance(&repo_path.join(".contexthub/prompt.d"))?;
+        let template = load_prompt_template(&repo_path.join(".contexthub/prompt.md"))?;
+        let cache = config
+            .ollama
+            .cache
+            .then(|| ResponseCache::new(repo_path.join(".contexthub/cache")));
+        let llm = LlmProcessor::new(config.ollama.clone())?
+            .with_guidance(guidance)
+            .with_template(template)
+            .with_cache(cache);
+        let embeddings_off = config.ollama.embedding_model.is_empty();
+        let notes = std::fs::read_to_string(repo_path.join(".contexthub/notes.md"))
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| format!("{}\n", text.trim()));
         
         Ok(Self {
             git,
             llm,
             storage,
             config,
+            notes,
+            branch: None,
+            embeddings_unavailable: AtomicBool::new(embeddings_off),
+        })
+    }
+
+    fn origin<'a>(&'a self, branch: Option<&'a str>) -> EntryOrigin<'a> {
+        EntryOrigin {
+            model: &self.config.ollama.model,
+            prompt_version: self.llm.prompt_version(),
+            branch,
+        }
+    }
+
+    /// Restrict exports to entries processed on `branch`
+    pub fn with_branch(mut self, branch: Option<String>) -> Self {
+        self.branch = branch;
+        self
+    }
+
+    /// Branch exports are limited to: the explicit `--branch`, else the
+    /// current branch when `context.isolate_by_branch` is on.
+    fn export_branch(&self) -> Option<String> {
+        self.branch.clone().or_else(|| {
+            if self.config.context.isolate_by_branch {
+                self.git.current_branch()
+            } else {
+                None
+            }
         })
     }
 
@@ -30,8 +136,9 @@ impl ContextProcessor {
         self.git.get_commit_history(limit)
     }
 
-    pub fn get_commit_range(&self, from: &str, to: &str) -> anyhow::Result<Vec<CommitInfo>> {
-        self.git.get_commit_range(from, to)
+    /// Commits for a `sync --from` revision or `A..B` range
+    pub fn get_revision_range(&self, spec: &str) -> anyhow::Result<Vec<CommitInfo>> {
+        self.git.get_revision_range(spec)
     }
 
     /// Check if a commit has already been stored (for dedup)
@@ -39,49 +146,380 @@ impl ContextProcessor {
         self.storage.has_commit(commit_hash)
     }
 
-    pub async fn process_commit(&self, commit: &CommitInfo) -> anyhow::Result<ExtractedContext> {
-        let diff = self.git.get_diff(&commit.hash)?;
+    /// `prefetched_diff` is the commit's `get_diff` output if the caller
+    /// already has it; otherwise the diff is read here.
+    pub async fn process_commit(
+        &self,
+        commit: &CommitInfo,
+        prefetched_diff: Option<String>,
+    ) -> anyhow::Result<ExtractedContext> {
+        let (insertions, deletions) = self.git.diff_stats(&commit.hash)?;
+        let commit = &CommitInfo {
+            insertions: Some(insertions),
+            deletions: Some(deletions),
+            ..commit.clone()
+        };
 
-        // Estimate token count and truncate diff if necessary
-        let max_tokens = self.config.context.max_tokens_per_commit;
-        let estimated_tokens = diff.len() / 4; // rough chars-to-tokens ratio
-        let diff = if estimated_tokens > max_tokens {
-            let max_chars = max_tokens * 4;
-            let truncated = &diff[..max_chars.min(diff.len())];
-            format!("{}\n\n[... diff truncated, {} tokens estimated, limit {}]", truncated, estimated_tokens, max_tokens)
-        } else {
-            diff
+        // Permission flips and symlink retargets have no content for the LLM
+        if let Some((summary, files)) = self.git.metadata_only_summary(&commit.hash)? {
+            let context = ExtractedContext {
+                summary,
+                files_changed: files.clone(),
+                key_details: vec![],
+                technologies: vec![],
+                impact: "low".to_string(),
+            };
+            self.store_context(commit, &context, &files)?;
+            return Ok(context);
+        }
+
+        if let Some(files) = self.git.ignored_only_files(&commit.hash)? {
+            let context = ExtractedContext {
+                summary: format!("Changes only in ignored paths ({} file(s))", files.len()),
+                files_changed: files.clone(),
+                key_details: vec![],
+                technologies: vec![],
+                impact: "low".to_string(),
+            };
+            self.store_context(commit, &context, &files)?;
+            return Ok(context);
+        }
+
+        if let Some(files) = self.git.whitespace_only_files(&commit.hash)? {
+            let context = ExtractedContext {
+                summary: format!("Formatting/whitespace-only change in {} file(s)", files.len()),
+                files_changed: files.clone(),
+                key_details: vec![],
+                technologies: vec![],
+                impact: "low".to_string(),
+            };
+            self.store_context(commit, &context, &files)?;
+            return Ok(context);
+        }
+
+        let raw_diff = match prefetched_diff {
+            Some(diff) => diff,
+            None => self.git.get_diff(&commit.hash)?,
         };
 
-        let files: Vec<String> = diff
-            .lines()
-            .filter(|l| l.starts_with("+++ b/") || l.starts_with("--- a/"))
-            .map(|l| l.replace("+++ b/", "").replace("--- a/", ""))
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
+        // A root commit diffs against the empty tree, i.e. the whole initial
+        // codebase. If that blows the budget, a truncated fragment tells the
+        // LLM little, so describe the layout instead.
+        if commit.parent_hashes.is_empty()
+            && raw_diff.len() > self.config.context.diff_byte_budget()
+        {
+            let context = self.scaffold_context(commit)?;
+            let files = context.files_changed.clone();
+            self.store_context(commit, &context, &files)?;
+            return Ok(context);
+        }
+
+        let diff = self.truncate_diff(raw_diff);
+        let files = Self::files_from_diff(&diff);
 
         // Fetch previous context for incremental chaining
-        let previous_context = self.storage.get_latest_context_summary()?;
-
-        let context = self.llm
-            .extract_context(
-                &commit.message,
-                &diff,
-                &files,
-                previous_context.as_deref(),
+        let previous_context = self.previous_context()?;
+
+        let result = self
+            .extract_within_window(commit, &diff, &files, previous_context.as_deref())
+            .await;
+        if self.config.context.store_diffs {
+            self.storage.store_diff(&commit.hash, &diff)?;
+        }
+
+        let context = match result {
+            Ok(context) => context,
+            Err(e) if e.is::<EmptyLlmResponse>() => {
+                let branch = self.git.current_branch();
+                self.storage.store_failed_context(
+                    commit,
+                    &e.to_string(),
+                    &files,
+                    &self.origin(branch.as_deref()),
+                )?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.store_context(commit, &context, &files)?;
+        self.embed_stored(&commit.hash).await?;
+
+        Ok(context)
+    }
+
+    /// Embed the stored entry for `commit_hash`. A failure is logged and
+    /// turns embedding off for the rest of the run instead of failing the sync.
+    async fn embed_stored(&self, commit_hash: &str) -> anyhow::Result<()> {
+        if self.embeddings_unavailable.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let Some(ctx) = self.storage.get_context_by_hash(commit_hash)? else {
+            return Ok(());
+        };
+        if let Err(e) = self.embed_entry(&ctx).await {
+            if !self.embeddings_unavailable.swap(true, Ordering::Relaxed) {
+                log::warn!("Skipping embeddings for this run: {:#}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn embed_entry(&self, ctx: &GlobalContext) -> anyhow::Result<()> {
+        let vector = self.llm.embed(&ctx.embedding_text()).await?;
+        self.storage
+            .store_embedding(&ctx.commit_hash, &self.config.ollama.embedding_model, &vector)
+    }
+
+    /// Embed the entries that have no vector from the configured embedding
+    /// model yet, newest first, stopping at the first failure. Returns how
+    /// many were embedded and how many are still missing.
+    pub async fn backfill_embeddings(&self) -> anyhow::Result<(usize, usize)> {
+        let pending = self.storage.get_unembedded(&self.config.ollama.embedding_model)?;
+        for (done, ctx) in pending.iter().enumerate() {
+            if let Err(e) = self.embed_entry(ctx).await {
+                log::warn!("Stopped embedding entries: {:#}", e);
+                return Ok((done, pending.len() - done));
+            }
+        }
+        Ok((pending.len(), 0))
+    }
+
+    /// The `top_k` entries closest in meaning to `query` (see
+    /// `Storage::semantic_search`). Call `backfill_embeddings` first to
+    /// include entries stored without a vector.
+    pub async fn semantic_search(&self, query: &str, top_k: usize) -> anyhow::Result<Vec<SemanticHit>> {
+        let query_vec = self.llm.embed(query).await.with_context(|| {
+            format!(
+                "Could not embed the query with '{}'",
+                self.config.ollama.embedding_model
             )
-            .await?;
+        })?;
+        self.storage.semantic_search(&query_vec, top_k)
+    }
 
-        // Store full ExtractedContext as JSON in llm_extracted_context column
-        let extracted_json = serde_json::to_string(&context)?;
+    /// Entries that `reprompt` can rebuild, oldest first
+    pub fn get_reprompt_entries(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        self.storage.get_global_context_with_diffs()
+    }
 
-        self.storage.store_global_context(
-            commit,
-            &context.summary,
-            &files,
-            &extracted_json,
-        )?;
+    /// The diff saved for `commit_hash` by `context.store_diffs`, if any
+    pub fn stored_diff(&self, commit_hash: &str) -> anyhow::Result<Option<String>> {
+        self.storage.get_stored_diff(commit_hash)
+    }
+
+    /// Turn a stored entry and its stored diff into a `RepromptJob`. Pure, so
+    /// callers can prepare many entries in parallel.
+    pub fn prepare_reprompt(ctx: &GlobalContext, diff: String) -> RepromptJob {
+        let commit = stored_commit(ctx);
+        let files = Self::files_from_diff(&diff);
+        RepromptJob { commit, diff, files }
+    }
+
+    /// Copy the high-impact entries missing from `global` into it, tagged
+    /// `repo:<repo_name>`. Entries already there are left as they are.
+    /// Returns the number copied.
+    pub fn mirror_high_impact(&self, global: &Storage, repo_name: &str) -> anyhow::Result<usize> {
+        let repo_tag = format!("{}{}", REPO_TAG_PREFIX, repo_name.to_lowercase());
+        let mut mirrored = 0;
+        for ctx in self.storage.get_global_context()? {
+            if entry_impact_rank(&ctx) != impact_rank("high") || global.has_commit(&ctx.commit_hash)? {
+                continue;
+            }
+            let extracted = ExtractedContext {
+                summary: ctx.context_summary.clone(),
+                files_changed: serde_json::from_str(&ctx.files_changed).unwrap_or_default(),
+                key_details: ctx.key_details.clone().unwrap_or_default(),
+                technologies: ctx.technologies.clone().unwrap_or_default(),
+                impact: "high".to_string(),
+            };
+            let origin = EntryOrigin {
+                model: ctx.model.as_deref().unwrap_or(""),
+                prompt_version: ctx.prompt_version.as_deref().unwrap_or(""),
+                branch: ctx.branch.as_deref(),
+            };
+            global.store_global_context(&stored_commit(&ctx), &extracted, &extracted.files_changed, &origin)?;
+            global.store_tags(&ctx.commit_hash, std::slice::from_ref(&repo_tag))?;
+            mirrored += 1;
+        }
+        Ok(mirrored)
+    }
+
+    /// Re-run extraction for a prepared entry with the current model and
+    /// prompt. Nothing is written; see `store_reprompt`.
+    pub async fn extract_reprompt(&self, job: &RepromptJob) -> anyhow::Result<ExtractedContext> {
+        let previous_context = self.previous_context()?;
+        self.extract_within_window(&job.commit, &job.diff, &job.files, previous_context.as_deref())
+            .await
+    }
+
+    /// Replace the stored entry for a re-prompted commit
+    pub fn store_reprompt(&self, job: &RepromptJob, context: &ExtractedContext) -> anyhow::Result<()> {
+        self.store_context(&job.commit, context, &job.files)
+    }
+
+    /// Ollama silently drops whatever doesn't fit in `num_ctx`, so a prompt
+    /// that would overflow is split by file and the partial results merged.
+    async fn extract_within_window(
+        &self,
+        commit: &CommitInfo,
+        diff: &str,
+        files: &[String],
+        previous_context: Option<&str>,
+    ) -> anyhow::Result<ExtractedContext> {
+        let window = self.config.ollama.context_window();
+        let message = truncate_message(&commit.message, self.config.context.max_message_chars);
+        let estimated = self
+            .llm
+            .estimate_prompt_tokens(&message, diff, files, previous_context);
+        if estimated <= window {
+            return self
+                .llm
+                .extract_context(&message, diff, files, previous_context)
+                .await;
+        }
+
+        let overhead = estimated.saturating_sub(diff.len() / 4);
+        let chunk_chars = window.saturating_sub(overhead).max(MIN_CHUNK_TOKENS) * 4;
+        let chunks = Self::chunk_diff(diff, chunk_chars);
+        log::info!(
+            "Commit {} needs ~{} tokens but num_ctx is {}; analyzing in {} parts",
+            commit.short_hash,
+            estimated,
+            window,
+            chunks.len()
+        );
+
+        let mut parts = Vec::with_capacity(chunks.len());
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let part_message = format!("{} (part {}/{})", message, idx + 1, chunks.len());
+            let chunk_files = Self::files_from_diff(chunk);
+            parts.push(
+                self.llm
+                    .extract_context(&part_message, chunk, &chunk_files, previous_context)
+                    .await?,
+            );
+        }
+
+        Ok(Self::merge_contexts(parts))
+    }
+
+    /// Split a diff at file boundaries into pieces of at most `max_chars`,
+    /// packing small files together. A single oversized file is cut short,
+    /// ending in a `[… truncated N bytes of <path>]` line.
+    fn chunk_diff(diff: &str, max_chars: usize) -> Vec<String> {
+        let mut sections: Vec<&str> = Vec::new();
+        let mut start = 0;
+        for (idx, _) in diff.match_indices("\ndiff --git ") {
+            sections.push(&diff[start..=idx]);
+            start = idx + 1;
+        }
+        sections.push(&diff[start..]);
+
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+        for section in sections {
+            let section = if section.len() > max_chars {
+                Cow::Owned(Self::truncate_section(section, max_chars))
+            } else {
+                Cow::Borrowed(section)
+            };
+            if !current.is_empty() && current.len() + section.len() > max_chars {
+                chunks.push(std::mem::take(&mut current));
+            }
+            current.push_str(&section);
+        }
+        if !current.is_empty() {
+            chunks.push(current);
+        }
+        chunks
+    }
+
+    /// Cut one file's diff section to at most `max_chars`, marker included
+    fn truncate_section(section: &str, max_chars: usize) -> String {
+        let path = section
+            .lines()
+            .next()
+            .and_then(|header| header.split(" b/").nth(1))
+            .unwrap_or("unknown file");
+        // The marker's byte count never has more digits than the section length
+        let reserve = format!("\n[… truncated {} bytes of {}]\n", section.len(), path).len();
+        let budget = max_chars.saturating_sub(reserve);
+        let cut = (0..=budget).rev().find(|&i| section.is_char_boundary(i)).unwrap_or(0);
+        let omitted = section.len() - cut;
+        log::warn!("Diff of {} exceeds the chunk size; dropped its last {} bytes", path, omitted);
+
+        let mut out = section[..cut].to_string();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&format!("[… truncated {} bytes of {}]\n", omitted, path));
+        out
+    }
+
+    /// Combine per-chunk results into one entry for the commit
+    fn merge_contexts(parts: Vec<ExtractedContext>) -> ExtractedContext {
+        fn push_unique(into: &mut Vec<String>, items: Vec<String>) {
+            for item in items {
+                if !into.contains(&item) {
+                    into.push(item);
+                }
+            }
+        }
+        let rank = |impact: &str| match impact {
+            "high" => 2,
+            "medium" => 1,
+            _ => 0,
+        };
+
+        let mut merged = ExtractedContext {
+            summary: String::new(),
+            files_changed: vec![],
+            key_details: vec![],
+            technologies: vec![],
+            impact: "low".to_string(),
+        };
+        let mut summaries = Vec::new();
+        for part in parts {
+            summaries.push(part.summary);
+            push_unique(&mut merged.files_changed, part.files_changed);
+            push_unique(&mut merged.key_details, part.key_details);
+            push_unique(&mut merged.technologies, part.technologies);
+            if rank(&part.impact) > rank(&merged.impact) {
+                merged.impact = part.impact;
+            }
+        }
+        merged.summary = summaries.join(" ");
+        merged
+    }
+
+    fn scaffold_context(&self, commit: &CommitInfo) -> anyhow::Result<ExtractedContext> {
+        let (top_level, file_count) = self.git.tree_overview(&commit.hash)?;
+        Ok(ExtractedContext {
+            summary: format!(
+                "Initial commit: project scaffold with {} file(s) ({})",
+                file_count,
+                top_level.join(", ")
+            ),
+            files_changed: top_level,
+            key_details: vec![format!(
+                "Root commit too large to analyze ({} files); top-level layout recorded instead",
+                file_count
+            )],
+            technologies: vec![],
+            impact: "high".to_string(),
+        })
+    }
+
+    fn store_context(
+        &self,
+        commit: &CommitInfo,
+        context: &ExtractedContext,
+        files: &[String],
+    ) -> anyhow::Result<()> {
+        let branch = self.git.current_branch();
+
+        self.storage.store_global_context(commit, context, files, &self.origin(branch.as_deref()))?;
 
         self.storage.store_ttl_memory(
             &commit.hash,
@@ -89,30 +527,223 @@ impl ContextProcessor {
             self.config.context.ttl_days,
         )?;
 
-        Ok(context)
+        self.storage.store_tags(&commit.hash, &parse_tags(&commit.message))?;
+
+        Ok(())
+    }
+
+    /// Join the last `chaining_depth` summaries, dropping the oldest until they
+    /// fit in the per-commit token budget. Depth 0 or `incremental_chaining`
+    /// off disables chaining.
+    fn previous_context(&self) -> anyhow::Result<Option<String>> {
+        let depth = self.config.context.effective_chaining_depth();
+        if depth == 0 {
+            return Ok(None);
+        }
+
+        let mut summaries = self.storage.get_recent_summaries(depth)?;
+        let max_chars = self.config.context.max_tokens_per_commit * 4;
+        while summaries.len() > 1 && summaries.iter().map(|s| s.len() + 1).sum::<usize>() > max_chars {
+            summaries.remove(0);
+        }
+
+        if summaries.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(summaries.join("\n")))
+        }
+    }
+
+    /// Summarize everything between two revisions without storing the result.
+    pub async fn summarize_range(&self, base: &str, target: &str) -> anyhow::Result<ExtractedContext> {
+        let diff = self.truncate_diff(self.git.get_diff_between(base, target)?);
+        let files = Self::files_from_diff(&diff);
+        let message = format!("Combined changes from {} to {}", base, target);
+
+        self.llm.extract_overview(&message, &diff, &files).await
+    }
+
+    /// Entries for commits that touched `file`: those whose recorded files
+    /// include it, plus those git history says changed it (the recorded list
+    /// can be cut short by diff truncation).
+    pub fn file_history(&self, file: &str) -> anyhow::Result<FileHistory> {
+        let mut entries = self.storage.get_global_context_for_file(file)?;
+        let mut unsynced = 0;
+        for hash in self.git.file_history(file)? {
+            if entries.iter().any(|e| e.commit_hash == hash) {
+                continue;
+            }
+            match self.storage.get_context_by_hash(&hash)? {
+                Some(entry) if !entry.extraction_failed => entries.push(entry),
+                Some(_) => {}
+                None => unsynced += 1,
+            }
+        }
+        entries.sort_by_key(|e| e.commit_date);
+        Ok(FileHistory { entries, unsynced })
+    }
+
+    /// Ask the LLM for the story of `file` across its stored entries
+    pub async fn file_overview(
+        &self,
+        file: &str,
+        entries: &[GlobalContext],
+    ) -> anyhow::Result<ExtractedContext> {
+        let history = entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} {} {}\n  {}",
+                    &e.commit_hash[..7.min(e.commit_hash.len())],
+                    e.commit_date.format("%Y-%m-%d"),
+                    e.commit_message.lines().next().unwrap_or(""),
+                    e.context_summary
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let message = format!(
+            "History of {} across {} commits, oldest first. Summarize how the file evolved and what it is for.",
+            file,
+            entries.len()
+        );
+
+        self.llm
+            .extract_overview(&message, &self.truncate_diff(history), &[file.to_string()])
+            .await
+    }
+
+    /// Cut the diff to the per-commit budget (see `ContextConfig::diff_byte_budget`)
+    fn truncate_diff(&self, diff: String) -> String {
+        let max_bytes = self.config.context.diff_byte_budget();
+        match truncate_diff_fairly(&diff, max_bytes) {
+            Some(truncated) => {
+                log::info!(
+                    "Diff truncated from {} to {} bytes (limit {})",
+                    diff.len(),
+                    truncated.len(),
+                    max_bytes
+                );
+                truncated
+            }
+            None => diff,
+        }
+    }
+
+    fn files_from_diff(diff: &str) -> Vec<String> {
+        diff.lines()
+            .filter_map(|l| {
+                l.strip_prefix("+++ b/")
+                    .or_else(|| l.strip_prefix("--- a/"))
+                    .or_else(|| l.strip_prefix(BINARY_FILE_NOTE))
+            })
+            .map(|path| path.to_string())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// The stored LLM output for a full hash (see `Storage::get_extracted_context`)
+    pub fn get_extracted_context(&self, commit_hash: &str) -> anyhow::Result<Option<ExtractedContext>> {
+        self.storage.get_extracted_context(commit_hash)
+    }
+
+    pub fn get_context_by_hash(&self, hash_prefix: &str) -> anyhow::Result<Option<GlobalContext>> {
+        self.storage.get_context_by_hash(hash_prefix)
+    }
+
+    /// Store a hand-written summary for `commit_hash` (see `Storage::update_summary`)
+    pub fn update_summary(&self, commit_hash: &str, summary: &str) -> anyhow::Result<bool> {
+        self.storage.update_summary(commit_hash, summary)
+    }
+
+    /// Delete one entry (see `Storage::delete_context`)
+    pub fn delete_context(&self, hash_prefix: &str) -> anyhow::Result<DeletedRows> {
+        self.storage.delete_context(hash_prefix)
+    }
+
+    /// Full-text search over stored entries (see `Storage::search`)
+    pub fn search(&self, query: &str, limit: usize) -> anyhow::Result<Vec<SearchHit>> {
+        self.storage.search(query, limit)
+    }
+
+    /// Version of the extraction prompt new entries are stamped with
+    pub fn prompt_version(&self) -> &str {
+        self.llm.prompt_version()
+    }
+
+    /// Stored commits whose model or prompt version differs from the current config
+    pub fn get_stale_commits(&self) -> anyhow::Result<Vec<CommitInfo>> {
+        self.storage
+            .get_stale_commits(&self.config.ollama.model, self.llm.prompt_version())?
+            .iter()
+            .map(|hash| self.git.get_commit(hash))
+            .collect()
+    }
+
+    pub fn get_global_context_by_author(&self, author: &str) -> anyhow::Result<Vec<GlobalContext>> {
+        self.storage.get_global_context_by_author(author)
     }
 
     pub fn get_global_context(&self) -> anyhow::Result<Vec<GlobalContext>> {
         self.storage.get_global_context()
     }
 
+    pub fn count_expired_ttl(&self) -> anyhow::Result<usize> {
+        self.storage.count_expired_ttl()
+    }
+
     #[allow(dead_code)]
     pub fn get_global_context_since(&self, commit_hash: &str) -> anyhow::Result<Vec<GlobalContext>> {
         self.storage.get_global_context_since(commit_hash)
     }
 
-    pub fn export_context_markdown(&self) -> anyhow::Result<String> {
-        let contexts = self.storage.get_global_context()?;
+    /// Stored entries in `export.order`, without those below `export.min_impact`
+    /// and with near-duplicates collapsed when `export.dedup` is on
+    fn export_entries(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut contexts = self.storage.get_global_context()?;
+        if let Some(branch) = self.export_branch() {
+            retain_branch(&mut contexts, &branch);
+        }
+        let min_rank = impact_rank(&self.config.export.min_impact).unwrap_or(0);
+        if min_rank > 0 {
+            contexts.retain(|ctx| entry_impact_rank(ctx).is_none_or(|rank| rank >= min_rank));
+        }
+        if self.config.export.dedup {
+            contexts = dedup_similar(contexts, self.config.export.dedup_threshold);
+        }
+        if self.config.export.order == "impact" {
+            // Stable, so entries keep newest-first order within a level;
+            // unknown impact sorts after `low`
+            contexts.sort_by_cached_key(|ctx| {
+                std::cmp::Reverse(entry_impact_rank(ctx).map_or(0, |rank| rank + 1))
+            });
+        }
+        Ok(contexts)
+    }
+
+    pub fn export_context_markdown(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let contexts = self.export_entries()?;
         
         let mut output = String::from("# Repository Context\n\n");
+        let overview = self.overview_section();
+        if !overview.is_empty() {
+            output.push_str(&overview);
+            output.push('\n');
+        }
         output.push_str("## Recent Changes\n\n");
         
-        for ctx in contexts.iter().take(20) {
+        for ctx in contexts.iter().take(resolve_limit(limit, self.config.export.max_entries)) {
             output.push_str(&format!("### {}: {}\n", 
                 &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
                 ctx.commit_message.lines().next().unwrap_or("No message")
             ));
             output.push_str(&format!("- **Date:** {}\n", ctx.commit_date.format("%Y-%m-%d")));
+            if self.config.export.include_author {
+                if let Some(author) = &ctx.author {
+                    output.push_str(&format!("- **Author:** {}\n", author));
+                }
+            }
             output.push_str(&format!("- **Summary:** {}\n", ctx.context_summary));
             
             if !ctx.files_changed.is_empty() {
@@ -126,63 +757,356 @@ impl ContextProcessor {
         Ok(output)
     }
 
-    pub fn export_context_json(&self) -> anyhow::Result<String> {
-        let contexts = self.storage.get_global_context()?;
+    /// Unlike the other formats, JSON includes every entry unless `limit` is given
+    pub fn export_context_json(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let mut contexts = self.storage.get_global_context()?;
+        if let Some(branch) = self.export_branch() {
+            retain_branch(&mut contexts, &branch);
+        }
+        contexts.truncate(resolve_limit(limit, 0));
         let json = serde_json::to_string_pretty(&contexts)?;
         Ok(json)
     }
 
+    /// Input for an `export.custom` command: the same entries as the
+    /// assistant exports (filtered, ordered and capped by `max_entries`) as a
+    /// JSON array in the `json` export's shape
+    pub fn export_for_custom(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let mut contexts = self.export_entries()?;
+        contexts.truncate(resolve_limit(limit, self.config.export.max_entries));
+        Ok(serde_json::to_string_pretty(&contexts)?)
+    }
+
+    /// Stream entries as NDJSON (one JSON object per line) to `out` without
+    /// loading the whole history. Returns the number of lines written.
+    pub fn export_context_ndjson(
+        &self,
+        limit: Option<usize>,
+        out: &mut impl std::io::Write,
+    ) -> anyhow::Result<usize> {
+        let limit = resolve_limit(limit, 0);
+        let branch = self.export_branch();
+        let mut written = 0;
+        self.storage.for_each_global_context(|ctx| {
+            if written >= limit {
+                return Ok(false);
+            }
+            if let Some(branch) = &branch {
+                if ctx.branch.as_ref().is_some_and(|b| b != branch) {
+                    return Ok(true);
+                }
+            }
+            serde_json::to_writer(&mut *out, &ctx)?;
+            out.write_all(b"\n")?;
+            written += 1;
+            Ok(true)
+        })?;
+        out.flush()?;
+        Ok(written)
+    }
+
     /// Export context in CLAUDE.md format (for Claude Code / Claude AI)
-    pub fn export_for_claude(&self) -> anyhow::Result<String> {
-        let contexts = self.storage.get_global_context()?;
+    pub fn export_for_claude(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let contexts = self.export_entries()?;
+        Ok(self.render_claude(&contexts, limit))
+    }
+
+    /// Render CLAUDE.md content for an arbitrary set of entries (e.g. a filtered view)
+    pub fn render_claude(&self, contexts: &[GlobalContext], limit: Option<usize>) -> String {
+        self.claude_with(contexts, &self.shared_sections(contexts), limit)
+    }
+
+    /// Export context in .cursorrules format (for Cursor IDE)
+    pub fn export_for_cursor(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let contexts = self.export_entries()?;
+        Ok(self.cursor_with(&contexts, &self.shared_sections(&contexts), limit))
+    }
+
+    /// Export context for GitHub Copilot (.github/copilot-instructions.md)
+    pub fn export_for_copilot(&self, limit: Option<usize>) -> anyhow::Result<String> {
+        let contexts = self.export_entries()?;
+        Ok(self.copilot_with(&contexts, &self.shared_sections(&contexts), limit))
+    }
+
+    /// Render every assistant format from one read of the database, computing
+    /// the overview, file and technology blocks once for all of them.
+    pub fn export_assistants(&self, limit: Option<usize>) -> anyhow::Result<Vec<(&'static str, String)>> {
+        let contexts = self.export_entries()?;
+        let shared = self.shared_sections(&contexts);
+        Ok(vec![
+            ("claude", self.claude_with(&contexts, &shared, limit)),
+            ("cursor", self.cursor_with(&contexts, &shared, limit)),
+            ("copilot", self.copilot_with(&contexts, &shared, limit)),
+        ])
+    }
+
+    fn claude_with(&self, contexts: &[GlobalContext], shared: &SharedSections, limit: Option<usize>) -> String {
         let mut out = String::from("# CLAUDE.md — Project Context for Claude\n\n");
+        out.push_str(&self.generated_line());
         out.push_str("This file was auto-generated by ContextHub to help Claude understand this repository.\n\n");
-        out.push_str("## Project Overview\n\n");
-        out.push_str(&self.build_project_summary(&contexts));
-        out.push_str("\n## Recent Changes\n\n");
-        for ctx in contexts.iter().take(30) {
-            out.push_str(&format!("- **{}** ({}): {}\n",
+        let recent: String = contexts
+            .iter()
+            .take(resolve_limit(limit, self.config.export.claude_max()))
+            .map(|ctx| format!("- **{}** ({}): {}\n",
                 &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
                 ctx.commit_date.format("%Y-%m-%d"),
                 ctx.context_summary,
-            ));
-        }
-        out.push_str("\n## Key Technologies\n\n");
-        out.push_str(&self.extract_technologies(&contexts));
-        Ok(out)
+            ))
+            .collect();
+        out.push_str(&self.render_sections(
+            shared,
+            &recent,
+            ["Project Overview", "Recent Changes", "Key Technologies"],
+        ));
+        out
     }
 
-    /// Export context in .cursorrules format (for Cursor IDE)
-    pub fn export_for_cursor(&self) -> anyhow::Result<String> {
-        let contexts = self.storage.get_global_context()?;
+    fn cursor_with(&self, contexts: &[GlobalContext], shared: &SharedSections, limit: Option<usize>) -> String {
         let mut out = String::from("# Cursor Rules — Auto-generated by ContextHub\n\n");
-        out.push_str("## Project Context\n\n");
-        out.push_str(&self.build_project_summary(&contexts));
-        out.push_str("\n## Recent Development Activity\n\n");
-        for ctx in contexts.iter().take(20) {
-            out.push_str(&format!("- {}: {}\n",
+        out.push_str(&self.generated_line());
+        let contexts = &contexts[..contexts.len().min(resolve_limit(limit, self.config.export.cursor_max()))];
+        out.push_str(&format!("## Rules\n\n{}\n", Self::cursor_rules(contexts)));
+        // The summaries stay below the rules as supporting context
+        let recent: String = contexts
+            .iter()
+            .map(|ctx| format!("- {}: {}\n",
                 &ctx.commit_hash[..7.min(ctx.commit_hash.len())],
                 ctx.context_summary,
+            ))
+            .collect();
+        out.push_str(&self.render_sections(
+            shared,
+            &recent,
+            ["Project Context", "Recent Development Activity", "Technologies"],
+        ));
+        out
+    }
+
+    /// Imperative rules from the stored technologies and impact: a "Use X"
+    /// line per technology, most used first, and a "When touching …" line
+    /// per high-impact change that lists its files
+    fn cursor_rules(contexts: &[GlobalContext]) -> String {
+        const MAX_TECHNOLOGY_RULES: usize = 8;
+        const MAX_IMPACT_RULES: usize = 5;
+
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for tech in contexts.iter().flat_map(|ctx| ctx.technologies.iter().flatten()) {
+            match counts.iter_mut().find(|(seen, _)| seen.eq_ignore_ascii_case(tech)) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((tech, 1)),
+            }
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut out = String::new();
+        for (tech, count) in counts.iter().take(MAX_TECHNOLOGY_RULES) {
+            out.push_str(&format!(
+                "- Use {} the way the existing code does ({} recorded change{})\n",
+                tech,
+                count,
+                if *count == 1 { "" } else { "s" }
             ));
         }
-        out.push_str("\n## Technologies\n\n");
-        out.push_str(&self.extract_technologies(&contexts));
-        Ok(out)
+
+        let high = impact_rank("high");
+        let high_impact = contexts.iter().filter(|ctx| entry_impact_rank(ctx) == high);
+        for ctx in high_impact.take(MAX_IMPACT_RULES) {
+            let files: Vec<String> = serde_json::from_str(&ctx.files_changed).unwrap_or_default();
+            if files.is_empty() {
+                continue;
+            }
+            let mut touched: Vec<String> = files.iter().take(3).map(|f| format!("`{}`", f)).collect();
+            if files.len() > 3 {
+                touched.push(format!("{} more", files.len() - 3));
+            }
+            out.push_str(&format!(
+                "- When touching {}, keep this high-impact change in mind: {}\n",
+                touched.join(", "),
+                truncate_summary(&ctx.context_summary, 200)
+            ));
+        }
+
+        if out.is_empty() {
+            out.push_str("- Follow the conventions of the surrounding code; no context has been synced yet\n");
+        }
+        out
     }
 
-    /// Export context for GitHub Copilot (.github/copilot-instructions.md)
-    pub fn export_for_copilot(&self) -> anyhow::Result<String> {
-        let contexts = self.storage.get_global_context()?;
+    fn copilot_with(&self, contexts: &[GlobalContext], shared: &SharedSections, limit: Option<usize>) -> String {
         let mut out = String::from("# Copilot Instructions — Auto-generated by ContextHub\n\n");
-        out.push_str("## Repository Context\n\n");
-        out.push_str(&self.build_project_summary(&contexts));
-        out.push_str("\n## Recent Changes Summary\n\n");
-        for ctx in contexts.iter().take(15) {
-            out.push_str(&format!("- {}\n", ctx.context_summary));
+        out.push_str(&self.generated_line());
+        let recent: String = contexts
+            .iter()
+            .take(resolve_limit(limit, self.config.export.copilot_max()))
+            .map(|ctx| format!("- {}\n", ctx.context_summary))
+            .collect();
+        out.push_str(&self.render_sections(
+            shared,
+            &recent,
+            ["Repository Context", "Recent Changes Summary", "Technologies & Patterns"],
+        ));
+        out
+    }
+
+    /// `context.project_overview` as an "## Overview" section in the shape of
+    /// the other export sections; empty when no overview is set
+    fn overview_section(&self) -> String {
+        let overview = self.config.context.project_overview.trim();
+        if overview.is_empty() {
+            return String::new();
+        }
+        format!("## Overview\n\n{}\n", overview)
+    }
+
+    /// The `export.timestamp` line; empty unless enabled, so output only
+    /// changes when the stored context does
+    fn generated_line(&self) -> String {
+        if !self.config.export.timestamp {
+            return String::new();
+        }
+        format!("Generated: {}\n\n", chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"))
+    }
+
+    /// Compute the format-independent blocks; disabled sections are skipped.
+    fn shared_sections(&self, contexts: &[GlobalContext]) -> SharedSections {
+        SharedSections {
+            overview: self
+                .section_enabled("overview")
+                .then(|| self.build_project_summary(contexts)),
+            files: self.section_enabled("files").then(|| self.key_files(contexts)),
+            technologies: self
+                .section_enabled("technologies")
+                .then(|| self.extract_technologies(contexts)),
         }
-        out.push_str("\n## Technologies & Patterns\n\n");
-        out.push_str(&self.extract_technologies(&contexts));
-        Ok(out)
+    }
+
+    fn section_enabled(&self, name: &str) -> bool {
+        self.config.export.sections.iter().any(|s| s == name)
+    }
+
+    /// The `export.sections` blocks shared by the assistant exports.
+    /// `headings` names the overview, recent-changes and technologies
+    /// sections in the format's own wording.
+    fn render_sections(
+        &self,
+        shared: &SharedSections,
+        recent: &str,
+        headings: [&str; 3],
+    ) -> String {
+        let [overview, recent_changes, technologies] = headings;
+        let mut out = self.overview_section();
+
+        for section in EXPORT_SECTIONS {
+            let (heading, body) = match *section {
+                "overview" => (overview, shared.overview.as_deref()),
+                "files" => ("Key Files", shared.files.as_deref()),
+                "recent_changes" => (
+                    recent_changes,
+                    // Like the other blocks, say so rather than leave an empty heading
+                    self.section_enabled("recent_changes")
+                        .then_some(if recent.is_empty() { "No changes synced yet.\n" } else { recent }),
+                ),
+                "technologies" => (technologies, shared.technologies.as_deref()),
+                "notes" => ("Notes", self.notes.as_deref().filter(|_| self.section_enabled("notes"))),
+                _ => continue,
+            };
+            let Some(body) = body else { continue };
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("## {}\n\n{}", heading, body));
+        }
+        out
+    }
+
+    /// Write one Obsidian note per entry into `dir`, named by short hash, with
+    /// YAML frontmatter and `[[wikilinks]]` to neighbouring commits and to
+    /// commits touching the same files. Returns the number of notes written.
+    pub fn export_for_obsidian(&self, dir: &Path, limit: Option<usize>) -> anyhow::Result<usize> {
+        let mut contexts = self.export_entries()?;
+        contexts.truncate(resolve_limit(limit, self.config.export.max_entries));
+        // `export.order` picks which entries make the cut; neighbour links
+        // still follow history
+        contexts.sort_by_key(|ctx| std::cmp::Reverse(ctx.commit_date));
+        std::fs::create_dir_all(dir)?;
+
+        let short = |ctx: &GlobalContext| ctx.commit_hash[..7.min(ctx.commit_hash.len())].to_string();
+        let files: Vec<HashSet<String>> = contexts
+            .iter()
+            .map(|ctx| {
+                serde_json::from_str::<Vec<String>>(&ctx.files_changed)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect()
+            })
+            .collect();
+
+        // Entries are newest first
+        for (idx, ctx) in contexts.iter().enumerate() {
+            let title = ctx.commit_message.lines().next().unwrap_or("No message");
+
+            let mut tags = vec!["contexthub".to_string()];
+            tags.extend(
+                ctx.technologies
+                    .iter()
+                    .flatten()
+                    .map(|t| obsidian_tag(t))
+                    .filter(|t| !t.is_empty()),
+            );
+            let mut seen = HashSet::new();
+            tags.retain(|t| seen.insert(t.clone()));
+
+            let mut note = String::from("---\n");
+            note.push_str(&format!("date: {}\n", ctx.commit_date.format("%Y-%m-%d")));
+            note.push_str(&format!("hash: {}\n", ctx.commit_hash));
+            if let Some(author) = &ctx.author {
+                note.push_str(&format!("author: {}\n", yaml_string(author)));
+            }
+            if let Some(impact) = &ctx.impact {
+                note.push_str(&format!("impact: {}\n", impact));
+            }
+            note.push_str(&format!("aliases: [{}]\n", yaml_string(title)));
+            note.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+            note.push_str("---\n\n");
+
+            note.push_str(&format!("# {}\n\n{}\n", title, ctx.context_summary));
+            if let Some(details) = ctx.key_details.as_ref().filter(|d| !d.is_empty()) {
+                note.push_str("\n## Key details\n\n");
+                for detail in details {
+                    note.push_str(&format!("- {}\n", detail));
+                }
+            }
+            if !files[idx].is_empty() {
+                let mut sorted: Vec<&String> = files[idx].iter().collect();
+                sorted.sort();
+                note.push_str("\n## Files\n\n");
+                for f in sorted {
+                    note.push_str(&format!("- `{}`\n", f));
+                }
+            }
+
+            let related: Vec<String> = contexts
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != idx && !files[idx].is_disjoint(&files[*j]))
+                .take(5)
+                .map(|(_, other)| format!("[[{}]]", short(other)))
+                .collect();
+            note.push_str("\n## Links\n\n");
+            if let Some(prev) = contexts.get(idx + 1) {
+                note.push_str(&format!("- Previous: [[{}]]\n", short(prev)));
+            }
+            if let Some(next) = idx.checked_sub(1).and_then(|j| contexts.get(j)) {
+                note.push_str(&format!("- Next: [[{}]]\n", short(next)));
+            }
+            if !related.is_empty() {
+                note.push_str(&format!("- Same files: {}\n", related.join(", ")));
+            }
+
+            write_export(&dir.join(format!("{}.md", short(ctx))), &note, false)?;
+        }
+
+        Ok(contexts.len())
     }
 
     /// Build a project summary from stored contexts
@@ -191,7 +1115,24 @@ impl ContextProcessor {
             return "No context available yet.\n".to_string();
         }
 
-        // Collect all file paths to infer project structure
+        format!(
+            "This project has had {} context entries extracted from git commits.\nFiles touched across analyzed commits: {}\n",
+            contexts.len(),
+            Self::touched_files(contexts).len()
+        )
+    }
+
+    /// The `files` section: files touched by recent entries
+    fn key_files(&self, contexts: &[GlobalContext]) -> String {
+        let files = Self::touched_files(contexts);
+        if files.is_empty() {
+            return "No file information extracted yet.\n".to_string();
+        }
+        files.iter().take(20).map(|f| format!("- {}\n", f)).collect()
+    }
+
+    /// Sorted, deduplicated file paths from the 50 most recent entries
+    fn touched_files(contexts: &[GlobalContext]) -> Vec<String> {
         let mut all_files: Vec<String> = Vec::new();
         for ctx in contexts.iter().take(50) {
             if let Ok(files) = serde_json::from_str::<Vec<String>>(&ctx.files_changed) {
@@ -200,30 +1141,43 @@ impl ContextProcessor {
         }
         all_files.sort();
         all_files.dedup();
+        all_files
+    }
 
-        let mut summary = String::new();
-        if !all_files.is_empty() {
-            summary.push_str(&format!("This project has had {} context entries extracted from git commits.\n", contexts.len()));
-            summary.push_str(&format!("Files touched across analyzed commits: {}\n\n", all_files.len()));
-            summary.push_str("Key files:\n");
-            for f in all_files.iter().take(20) {
-                summary.push_str(&format!("- {}\n", f));
+    /// How many entries mention each technology, most frequent first.
+    /// Names are grouped case-insensitively, keeping the first spelling seen.
+    pub fn technology_counts(&self) -> anyhow::Result<Vec<(String, usize)>> {
+        let mut counts: std::collections::HashMap<String, (String, usize)> =
+            std::collections::HashMap::new();
+
+        for ctx in self.storage.get_global_context()? {
+            // Count each technology once per entry
+            let techs: HashSet<String> = ctx
+                .technologies
+                .iter()
+                .flatten()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+            for tech in techs {
+                counts
+                    .entry(tech.to_lowercase())
+                    .or_insert_with(|| (tech.clone(), 0))
+                    .1 += 1;
             }
-            summary.push('\n');
         }
-        summary
+
+        let mut sorted: Vec<(String, usize)> = counts.into_values().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+        Ok(sorted)
     }
 
-    /// Extract unique technologies from stored ExtractedContext JSON
+    /// Unique technologies across the most recent entries
     fn extract_technologies(&self, contexts: &[GlobalContext]) -> String {
         let mut techs: std::collections::HashSet<String> = std::collections::HashSet::new();
 
         for ctx in contexts.iter().take(50) {
-            if let Ok(extracted) = serde_json::from_str::<crate::core::llm::ExtractedContext>(&ctx.llm_extracted_context) {
-                for tech in &extracted.technologies {
-                    techs.insert(tech.clone());
-                }
-            }
+            techs.extend(ctx.technologies.iter().flatten().cloned());
         }
 
         if techs.is_empty() {
@@ -235,15 +1189,438 @@ impl ContextProcessor {
         sorted.iter().map(|t| format!("- {}\n", t)).collect()
     }
 
-    pub fn is_ollama_running(&self) -> bool {
-        self.llm.is_ollama_running()
+    pub async fn is_ollama_running(&self) -> bool {
+        self.llm.is_ollama_running().await
     }
 
     pub fn get_last_commit(&self) -> anyhow::Result<Option<String>> {
         self.storage.get_last_processed_commit()
     }
 
+    pub fn get_failed_entries(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        self.storage.get_failed_contexts()
+    }
+
+    /// Re-run extraction for an entry flagged as failed: from its stored diff
+    /// if there is one, otherwise by re-reading the commit from git.
+    pub async fn retry_failed(&self, ctx: &GlobalContext) -> anyhow::Result<ExtractedContext> {
+        if let Some(diff) = self.stored_diff(&ctx.commit_hash)? {
+            let job = Self::prepare_reprompt(ctx, diff);
+            let context = self.extract_reprompt(&job).await?;
+            self.store_reprompt(&job, &context)?;
+            return Ok(context);
+        }
+        let commit = self.git.get_commit(&ctx.commit_hash).with_context(|| {
+            format!(
+                "Commit {} is no longer in the repository and has no stored diff",
+                &ctx.commit_hash[..7.min(ctx.commit_hash.len())]
+            )
+        })?;
+        self.process_commit(&commit, None).await
+    }
+
+    /// Full hashes of entries tagged `tag` (case-insensitive)
+    pub fn get_tagged_hashes(&self, tag: &str) -> anyhow::Result<HashSet<String>> {
+        Ok(self
+            .storage
+            .get_tagged_hashes(&tag.trim_start_matches('#').to_lowercase())?
+            .into_iter()
+            .collect())
+    }
+
+    pub fn get_tag_counts(&self) -> anyhow::Result<Vec<(String, usize)>> {
+        self.storage.get_tag_counts()
+    }
+
+    pub fn get_churn_totals(&self) -> anyhow::Result<(usize, usize, usize)> {
+        self.storage.get_churn_totals()
+    }
+
+    pub fn get_failed_count(&self) -> anyhow::Result<usize> {
+        self.storage.get_failed_count()
+    }
+
     pub fn get_context_count(&self) -> anyhow::Result<usize> {
         self.storage.get_context_count()
     }
 }
+
+/// A `CommitInfo` rebuilt from a stored row, for when the commit may no
+/// longer exist in git
+fn stored_commit(ctx: &GlobalContext) -> CommitInfo {
+    CommitInfo {
+        hash: ctx.commit_hash.clone(),
+        short_hash: ctx.commit_hash[..7.min(ctx.commit_hash.len())].to_string(),
+        message: ctx.commit_message.clone(),
+        author: ctx.author.clone().unwrap_or_default(),
+        date: ctx.commit_date,
+        parent_hashes: vec![],
+        insertions: ctx.insertions,
+        deletions: ctx.deletions,
+    }
+}
+
+/// Room kept for the markers `truncate_diff_fairly` adds
+const TRUNCATION_MARKER_BYTES: usize = 64;
+
+/// Cut `diff` to about `max_bytes`, sharing the budget fairly between files
+/// instead of dropping the tail: files smaller than an equal share are kept
+/// whole and the others split what is left, each cut at a line boundary.
+/// Files whose share can't hold their header and first hunk line are left
+/// out and counted in the closing marker. `None` if the diff already fits.
+pub fn truncate_diff_fairly(diff: &str, max_bytes: usize) -> Option<String> {
+    if diff.len() <= max_bytes {
+        return None;
+    }
+
+    // One chunk per file header or binary note. Anything before the first
+    // (e.g. submodule summaries) is treated like one more file.
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        let starts_file = line.starts_with("diff --git ") || line.starts_with(BINARY_FILE_NOTE);
+        if starts_file && offset > start {
+            chunks.push(&diff[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    chunks.push(&diff[start..]);
+
+    // Water-filling: the smallest chunks take what they need, the rest share
+    // the remainder evenly
+    let budget = max_bytes.saturating_sub(TRUNCATION_MARKER_BYTES);
+    let mut order: Vec<usize> = (0..chunks.len()).collect();
+    order.sort_by_key(|&i| chunks[i].len());
+    let mut shares = vec![0; chunks.len()];
+    let mut remaining = budget;
+    for (pos, &i) in order.iter().enumerate() {
+        let share = remaining / (order.len() - pos);
+        shares[i] = chunks[i].len().min(share);
+        remaining -= shares[i];
+    }
+
+    let mut out = String::new();
+    let mut omitted = 0;
+    for (chunk, share) in chunks.iter().zip(shares) {
+        if chunk.len() <= share {
+            out.push_str(chunk);
+            continue;
+        }
+        // A header or hunk line with nothing after it tells the LLM nothing,
+        // so only cut after an actual change line
+        let mut kept = 0;
+        let mut used = 0;
+        let mut in_hunk = !chunk.starts_with("diff --git ");
+        for line in chunk.split_inclusive('\n') {
+            if used + line.len() + TRUNCATION_MARKER_BYTES > share {
+                break;
+            }
+            used += line.len();
+            if line.starts_with("@@") {
+                in_hunk = true;
+            } else if in_hunk {
+                kept = used;
+            }
+        }
+        if kept > 0 {
+            out.push_str(&chunk[..kept]);
+            out.push_str("... [file truncated]\n");
+        } else {
+            omitted += 1;
+        }
+    }
+    out.push_str(&format!("... [diff truncated, {} files omitted]\n", omitted));
+    Some(out)
+}
+
+/// Flatten a summary to one line and cut it to `max_chars` (0 = no limit),
+/// ending in "…" when shortened.
+pub fn truncate_summary(summary: &str, max_chars: usize) -> String {
+    let flat = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    if max_chars == 0 || flat.chars().count() <= max_chars {
+        return flat;
+    }
+    let cut: String = flat.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Keep entries processed on `branch`. Entries without a recorded branch
+/// (synced before branches were tracked, or on a detached HEAD) are kept.
+pub fn retain_branch(contexts: &mut Vec<GlobalContext>, branch: &str) {
+    contexts.retain(|ctx| ctx.branch.as_deref().is_none_or(|b| b == branch));
+}
+
+/// Position of an impact value in `IMPACT_LEVELS`; `None` if unrecognized
+fn impact_rank(impact: &str) -> Option<usize> {
+    let impact = impact.trim().to_lowercase();
+    IMPACT_LEVELS.iter().position(|level| *level == impact)
+}
+
+/// `impact_rank` of an entry's extracted impact
+fn entry_impact_rank(ctx: &GlobalContext) -> Option<usize> {
+    ctx.impact.as_deref().and_then(impact_rank)
+}
+
+/// Tags from a commit message: `#word` hashtags (not `#123` issue refs) and
+/// comma-separated `Context:` trailer values, lowercased and deduplicated.
+pub fn parse_tags(message: &str) -> Vec<String> {
+    let hashtag = Regex::new(r"(?:^|\s)#([A-Za-z][\w-]*)").expect("valid tag pattern");
+    let trailer = Regex::new(r"(?im)^context:[ \t]*(.+)$").expect("valid trailer pattern");
+
+    let mut tags: Vec<String> = hashtag
+        .captures_iter(message)
+        .map(|c| c[1].to_string())
+        .chain(trailer.captures_iter(message).flat_map(|c| {
+            c[1].split(',').map(|t| t.trim().to_string()).collect::<Vec<_>>()
+        }))
+        .map(|t| t.trim_start_matches('#').to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Shorten a commit message for the prompt, keeping whole lines where possible
+/// and noting how much was cut. `max_chars` of 0 leaves it untouched.
+fn truncate_message(message: &str, max_chars: usize) -> std::borrow::Cow<'_, str> {
+    let total = message.chars().count();
+    if max_chars == 0 || total <= max_chars {
+        return std::borrow::Cow::Borrowed(message);
+    }
+
+    let mut kept = String::new();
+    let mut kept_chars = 0;
+    for line in message.lines() {
+        let len = line.chars().count() + 1;
+        if kept_chars + len > max_chars {
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+        kept_chars += len;
+    }
+    // A single huge first line: cut mid-line rather than send nothing
+    if kept.is_empty() {
+        kept = message.chars().take(max_chars).collect();
+        kept.push('\n');
+        kept_chars = max_chars;
+    }
+
+    std::borrow::Cow::Owned(format!(
+        "{}[... commit message truncated, {} of {} characters shown]",
+        kept, kept_chars.min(total), total
+    ))
+}
+
+/// Obsidian tags can't contain spaces and most punctuation.
+fn obsidian_tag(tech: &str) -> String {
+    tech.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+        .collect()
+}
+
+/// Double-quoted YAML scalar; JSON string escaping is valid YAML.
+fn yaml_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Collapse entries whose summaries are near-identical ("fixed typo",
+/// "fix typo in docs"). Input is newest first; the most recent entry of each
+/// group is kept and its summary annotated with the group size.
+fn dedup_similar(contexts: Vec<GlobalContext>, threshold: f64) -> Vec<GlobalContext> {
+    let mut kept: Vec<(GlobalContext, HashSet<String>, usize)> = Vec::new();
+
+    for ctx in contexts {
+        let grams = trigrams(&ctx.context_summary);
+        match kept.iter_mut().find(|(_, g, _)| jaccard(g, &grams) >= threshold) {
+            Some((_, _, count)) => *count += 1,
+            None => kept.push((ctx, grams, 1)),
+        }
+    }
+
+    kept.into_iter()
+        .map(|(mut ctx, _, count)| {
+            if count > 1 {
+                ctx.context_summary = format!("{} (×{} similar)", ctx.context_summary, count);
+            }
+            ctx
+        })
+        .collect()
+}
+
+fn trigrams(text: &str) -> HashSet<String> {
+    let normalized: Vec<char> = text
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    normalized.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let union = a.union(b).count();
+    a.intersection(b).count() as f64 / union as f64
+}
+
+const MANAGED_START: &str = "<!-- contexthub:start -->";
+const MANAGED_END: &str = "<!-- contexthub:end -->";
+
+/// Write an export file. If the file already has a managed block, or `append`
+/// is set, only the text between the `contexthub:start`/`end` markers is
+/// (re)written and anything the user added outside them is kept. A file that
+/// would not change is left untouched; returns whether it was written.
+pub fn write_export(out_path: &PathBuf, content: &str, append: bool) -> std::io::Result<bool> {
+    let existing = match std::fs::read_to_string(out_path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    let output = match &existing {
+        Some(text) if append || text.contains(MANAGED_START) => splice_managed_block(text, content),
+        None if append => splice_managed_block("", content),
+        _ => content.to_string(),
+    };
+    if existing.as_deref() == Some(output.as_str()) {
+        return Ok(false);
+    }
+    std::fs::write(out_path, output)?;
+    Ok(true)
+}
+
+/// Replace the managed block in `existing` with `content`, or append a new
+/// block when there isn't one yet.
+fn splice_managed_block(existing: &str, content: &str) -> String {
+    let block = format!("{}\n{}\n{}", MANAGED_START, content.trim_end(), MANAGED_END);
+
+    if let Some(start) = existing.find(MANAGED_START) {
+        if let Some(end) = existing[start..].find(MANAGED_END) {
+            let end = start + end + MANAGED_END.len();
+            return format!("{}{}{}", &existing[..start], block, &existing[end..]);
+        }
+    }
+
+    if existing.trim().is_empty() {
+        format!("{}\n", block)
+    } else {
+        let separator = if existing.ends_with('\n') { "\n" } else { "\n\n" };
+        format!("{}{}{}\n", existing, separator, block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::{offline_config, TestRepo};
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn mode_only_commit_is_stored_without_llm_call() {
+        let repo = TestRepo::new();
+        repo.write("run.sh", "echo hi\n");
+        repo.commit("Add script");
+        repo.set_executable("run.sh", true);
+        let hash = repo.commit("Make script executable");
+
+        // The endpoint doesn't answer, so reaching the LLM would fail
+        let processor = ContextProcessor::new(&repo.path(), offline_config()).unwrap();
+        let commit = processor.get_commits(1).unwrap().remove(0);
+        assert_eq!(commit.hash, hash);
+        let context = processor.process_commit(&commit, None).await.unwrap();
+
+        assert_eq!(context.summary, "Changed file mode: run.sh");
+        let stored = processor.get_context_by_hash(&hash).unwrap().expect("stored entry");
+        assert_eq!(stored.context_summary, "Changed file mode: run.sh");
+    }
+
+    #[tokio::test]
+    async fn oversized_root_commit_gets_scaffold_summary() {
+        let repo = TestRepo::new();
+        repo.write("src/main.rs", &"fn main() {}\n".repeat(200));
+        repo.write("README.md", "# demo\n");
+        let hash = repo.commit("Initial commit");
+
+        let mut config = offline_config();
+        config.context.max_diff_bytes = 400;
+        let processor = ContextProcessor::new(&repo.path(), config).unwrap();
+        let commit = processor.get_commits(1).unwrap().remove(0);
+        assert!(commit.parent_hashes.is_empty());
+        let context = processor.process_commit(&commit, None).await.unwrap();
+
+        let expected = "Initial commit: project scaffold with 2 file(s) (README.md, src/)";
+        assert_eq!(context.summary, expected);
+        let stored = processor.get_context_by_hash(&hash).unwrap().expect("stored entry");
+        assert_eq!(stored.context_summary, expected);
+    }
+
+    #[test]
+    fn chunk_diff_marks_an_oversized_file() {
+        let small = "diff --git a/a.txt b/a.txt\n+one\n";
+        let big = format!("diff --git a/big.rs b/big.rs\n{}", "+line\n".repeat(100));
+        let diff = format!("{}{}", small, big);
+
+        let chunks = ContextProcessor::chunk_diff(&diff, 200);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], small);
+        assert!(chunks[1].len() <= 200);
+        let (kept, marker) = chunks[1].rsplit_once("[… truncated ").expect("truncation marker");
+        let omitted: usize = marker.split(' ').next().unwrap().parse().unwrap();
+        assert_eq!(marker, format!("{} bytes of big.rs]\n", omitted));
+        assert!(kept.starts_with(&big[..big.len() - omitted]));
+    }
+
+    #[test]
+    fn cursor_export_derives_rules_from_technologies_and_impact() {
+        let repo = TestRepo::new();
+        let processor = ContextProcessor::new(&repo.path(), offline_config()).unwrap();
+        let store = |hash: &str, summary: &str, files: &[&str], technologies: &[&str], impact: &str| {
+            let commit = CommitInfo {
+                hash: hash.to_string(),
+                short_hash: hash[..7].to_string(),
+                message: summary.to_string(),
+                author: "Test".to_string(),
+                date: chrono::Utc::now(),
+                parent_hashes: vec![],
+                insertions: None,
+                deletions: None,
+            };
+            let files: Vec<String> = files.iter().map(|f| f.to_string()).collect();
+            let context = ExtractedContext {
+                summary: summary.to_string(),
+                files_changed: files.clone(),
+                key_details: vec![],
+                technologies: technologies.iter().map(|t| t.to_string()).collect(),
+                impact: impact.to_string(),
+            };
+            processor.storage.store_global_context(&commit, &context, &files, &processor.origin(None)).unwrap();
+        };
+        store("aaaaaaa1", "Add token refresh", &["src/auth.rs"], &["Rust", "tokio"], "high");
+        store("bbbbbbb2", "Fix typo", &["README.md"], &["Rust"], "low");
+
+        let rules = processor.export_for_cursor(None).unwrap();
+        assert!(rules.contains("## Rules\n\n- Use Rust the way the existing code does (2 recorded changes)\n- Use tokio the way the existing code does (1 recorded change)\n"), "{}", rules);
+        assert!(rules.contains("- When touching `src/auth.rs`, keep this high-impact change in mind: Add token refresh\n"));
+        assert!(!rules.contains("When touching `README.md`"));
+        // The summaries remain as supporting context
+        assert!(rules.contains("aaaaaaa: Add token refresh"));
+    }
+
+    #[test]
+    fn cursor_export_without_context_has_a_fallback_rule() {
+        let repo = TestRepo::new();
+        let processor = ContextProcessor::new(&repo.path(), offline_config()).unwrap();
+        let rules = processor.export_for_cursor(None).unwrap();
+        assert!(rules.contains("## Rules\n\n- Follow the conventions of the surrounding code"));
+    }
+}
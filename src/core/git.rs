@@ -1,6 +1,9 @@
-use git2::{DiffOptions, Repository, Sort};
+use anyhow::Context;
+use git2::{Delta, DiffOptions, FileMode, Repository, Sort};
 use std::path::PathBuf;
 
+use crate::utils::config::GitConfig;
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct CommitInfo {
@@ -10,88 +13,540 @@ pub struct CommitInfo {
     pub author: String,
     pub date: chrono::DateTime<chrono::Utc>,
     pub parent_hashes: Vec<String>,
+    /// Lines added and removed; `None` until `GitAnalyzer::diff_stats` fills
+    /// them in, which happens only for commits that are processed
+    pub insertions: Option<usize>,
+    pub deletions: Option<usize>,
+}
+
+/// Max submodule commit messages listed per gitlink bump
+const SUBMODULE_COMMIT_LIMIT: usize = 20;
+
+fn short(oid: &git2::Oid) -> String {
+    oid.to_string()[..7].to_string()
 }
 
 pub struct GitAnalyzer {
     repo: Repository,
+    config: GitConfig,
+    /// `context.ignore_globs`: paths left out of diffs sent to the LLM
+    ignore_globs: Vec<glob::Pattern>,
 }
 
+/// Glob matching for `ignore_globs`: like `.gitignore`, `*` stays within one
+/// path component and only `**` crosses directories
+const IGNORE_MATCH: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
 impl GitAnalyzer {
     pub fn new(path: &PathBuf) -> anyhow::Result<Self> {
+        Self::with_config(path, GitConfig::default())
+    }
+
+    pub fn with_config(path: &PathBuf, config: GitConfig) -> anyhow::Result<Self> {
         let repo = Repository::discover(path)?;
-        Ok(Self { repo })
+        Ok(Self {
+            repo,
+            config,
+            ignore_globs: Vec::new(),
+        })
+    }
+
+    /// Leave files matching any of `globs` out of diffs (see `IGNORE_MATCH`)
+    pub fn with_ignore_globs(mut self, globs: &[String]) -> anyhow::Result<Self> {
+        self.ignore_globs = globs
+            .iter()
+            .map(|g| glob::Pattern::new(g).with_context(|| format!("Invalid ignore glob '{}'", g)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(self)
+    }
+
+    /// A second handle on the same repository, e.g. for use on another thread.
+    pub fn reopen(&self) -> anyhow::Result<Self> {
+        let repo = Repository::open(self.repo.path())?;
+        Ok(Self {
+            repo,
+            config: self.config.clone(),
+            ignore_globs: self.ignore_globs.clone(),
+        })
+    }
+
+    fn is_ignored(&self, path: &std::path::Path) -> bool {
+        self.ignore_globs
+            .iter()
+            .any(|glob| glob.matches_path_with(path, IGNORE_MATCH))
     }
 
     pub fn get_commit_history(&self, limit: usize) -> anyhow::Result<Vec<CommitInfo>> {
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
-        revwalk.push_head()?;
+        let head = self.head_oid()?;
+        self.walk(head, None, limit, self.config.skip_merge_commits)?
+            .into_iter()
+            .map(|oid| self.commit_info(oid))
+            .collect()
+    }
+
+    /// Returns all commits reachable from HEAD authored after `since`, newest first.
+    pub fn get_commits_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<CommitInfo>> {
+        let head = self.head_oid()?;
 
         let mut commits = Vec::new();
-        for (idx, oid) in revwalk.enumerate() {
-            if idx >= limit {
-                break;
+        // Topological order isn't strictly chronological, so check every commit
+        for oid in self.walk(head, None, usize::MAX, self.config.skip_merge_commits)? {
+            let commit = self.commit_info(oid)?;
+            if commit.date > since {
+                commits.push(commit);
             }
+        }
 
-            let oid = oid?;
+        Ok(commits)
+    }
+
+    /// Hash of the commit reached by following first parents from HEAD back to
+    /// the start of history. New commits don't change it, so it identifies the
+    /// repository. `None` when there are no commits yet.
+    pub fn root_commit(&self) -> anyhow::Result<Option<String>> {
+        let Ok(head) = self.repo.head() else {
+            return Ok(None);
+        };
+        let mut commit = head.peel_to_commit()?;
+        while let Ok(parent) = commit.parent(0) {
+            commit = parent;
+        }
+        Ok(Some(commit.id().to_string()))
+    }
+
+    /// Commits reachable from HEAD that changed `file` (a repository-relative
+    /// file or directory), newest first. Renames are not followed; a merge
+    /// counts only if `file` differs from every parent.
+    pub fn file_history(&self, file: &str) -> anyhow::Result<Vec<String>> {
+        let path = std::path::Path::new(file);
+        let entry_id = |commit: &git2::Commit| {
+            commit.tree().ok().and_then(|tree| tree.get_path(path).ok()).map(|e| e.id())
+        };
+
+        let mut hashes = Vec::new();
+        for oid in self.walk(self.head_oid()?, None, usize::MAX, false)? {
             let commit = self.repo.find_commit(oid)?;
+            let current = entry_id(&commit);
+            let changed = if commit.parent_count() == 0 {
+                current.is_some()
+            } else {
+                commit.parents().all(|parent| entry_id(&parent) != current)
+            };
+            if changed {
+                hashes.push(oid.to_string());
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Short name of the checked-out branch; `None` when HEAD is detached or unborn
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        head.shorthand().map(|name| name.to_string())
+    }
+
+    /// Base for `summarize` when none is given: the remote's default branch
+    /// (`refs/remotes/origin/HEAD`), else the checked-out branch's upstream,
+    /// else a local `main` or `master`
+    pub fn default_base(&self) -> anyhow::Result<String> {
+        let resolves = |name: &String| self.repo.revparse_single(name).is_ok();
+        let remote_head = self
+            .repo
+            .find_reference("refs/remotes/origin/HEAD")
+            .ok()
+            .and_then(|r| r.symbolic_target().map(|t| t.to_string()))
+            .filter(resolves);
+        let upstream = || {
+            let head = self.repo.head().ok()?;
+            let name = self.repo.branch_upstream_name(head.name()?).ok()?;
+            name.as_str().map(|n| n.to_string()).filter(resolves)
+        };
+        let local = || {
+            ["refs/heads/main", "refs/heads/master"]
+                .into_iter()
+                .find(|name| self.repo.find_reference(name).is_ok())
+                .map(|name| name.to_string())
+        };
+
+        remote_head
+            .or_else(upstream)
+            .or_else(local)
+            .map(|name| {
+                name.strip_prefix("refs/remotes/")
+                    .or_else(|| name.strip_prefix("refs/heads/"))
+                    .unwrap_or(&name)
+                    .to_string()
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Could not find a default base branch (no origin/HEAD, upstream, main or master); pass --base"
+                )
+            })
+    }
 
-            let hash = oid.to_string();
-            let short_hash = hash[..7.min(hash.len())].to_string();
+    fn head_oid(&self) -> anyhow::Result<git2::Oid> {
+        self.repo
+            .head()?
+            .target()
+            .ok_or_else(|| anyhow::anyhow!("HEAD does not point at a commit"))
+    }
 
-            commits.push(CommitInfo {
-                hash: hash.clone(),
-                short_hash,
-                message: commit.message().unwrap_or("").trim().to_string(),
-                author: commit.author().name().unwrap_or("Unknown").to_string(),
-                date: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
-                    .unwrap_or_else(|| chrono::Utc::now()),
-                parent_hashes: commit.parents().map(|p| p.id().to_string()).collect(),
-            });
+    /// Up to `limit` commits reachable from `start` but not from `hide`,
+    /// newest first. With `first_parent`, only the first-parent chain is
+    /// followed, like `git log --first-parent`. With `skip_merges`, commits
+    /// with more than one parent are left out (their history is still walked).
+    fn walk(
+        &self,
+        start: git2::Oid,
+        hide: Option<git2::Oid>,
+        limit: usize,
+        skip_merges: bool,
+    ) -> anyhow::Result<Vec<git2::Oid>> {
+        if self.config.first_parent {
+            let mut oids = Vec::new();
+            let mut next = Some(start);
+            while let Some(oid) = next {
+                if oids.len() >= limit {
+                    break;
+                }
+                if let Some(hidden) = hide {
+                    if oid == hidden || self.repo.graph_descendant_of(hidden, oid)? {
+                        break;
+                    }
+                }
+                let commit = self.repo.find_commit(oid)?;
+                if !(skip_merges && commit.parent_count() > 1) {
+                    oids.push(oid);
+                }
+                next = commit.parent_ids().next();
+            }
+            return Ok(oids);
         }
 
-        Ok(commits)
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
+        revwalk.push(start)?;
+        if let Some(hidden) = hide {
+            revwalk.hide(hidden)?;
+        }
+        let mut oids = Vec::new();
+        for oid in revwalk {
+            if oids.len() >= limit {
+                break;
+            }
+            let oid = oid?;
+            if skip_merges && self.repo.find_commit(oid)?.parent_count() > 1 {
+                continue;
+            }
+            oids.push(oid);
+        }
+        Ok(oids)
+    }
+
+    pub fn get_commit(&self, commit_hash: &str) -> anyhow::Result<CommitInfo> {
+        self.commit_info(git2::Oid::from_str(commit_hash)?)
+    }
+
+    /// The subset of `hashes` that no longer resolve to a commit in this
+    /// repository, e.g. after a rebase or force-push rewrote history.
+    pub fn missing_commits(&self, hashes: &[String]) -> Vec<String> {
+        hashes
+            .iter()
+            .filter(|hash| {
+                git2::Oid::from_str(hash)
+                    .and_then(|oid| self.repo.find_commit(oid))
+                    .is_err()
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn commit_info(&self, oid: git2::Oid) -> anyhow::Result<CommitInfo> {
+        let commit = self.repo.find_commit(oid)?;
+        let hash = oid.to_string();
+        let short_hash = hash[..7.min(hash.len())].to_string();
+
+        let info = CommitInfo {
+            hash: hash.clone(),
+            short_hash,
+            message: commit.message().unwrap_or("").trim().to_string(),
+            author: commit.author().name().unwrap_or("Unknown").to_string(),
+            date: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .unwrap_or_else(chrono::Utc::now),
+            parent_hashes: commit.parents().map(|p| p.id().to_string()).collect(),
+            insertions: None,
+            deletions: None,
+        };
+        Ok(info)
     }
 
     /// Returns commits in the range (from_commit, to_commit], newest first.
     /// `from_commit` is exclusive (not included), `to_commit` is inclusive.
+    /// Both accept any revision (see `resolve_commit`).
     pub fn get_commit_range(
         &self,
         from_commit: &str,
         to_commit: &str,
     ) -> anyhow::Result<Vec<CommitInfo>> {
-        let from_oid = git2::Oid::from_str(from_commit)?;
-        let to_oid = git2::Oid::from_str(to_commit)?;
+        let from_oid = self.resolve_commit(from_commit)?;
+        let to_oid = self.resolve_commit(to_commit)?;
 
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
-        revwalk.push(to_oid)?;
         // Hide `from_oid` and all its ancestors — this gives us (from, to]
-        revwalk.hide(from_oid)?;
+        self.walk(to_oid, Some(from_oid), usize::MAX, self.config.skip_merge_commits)?
+            .into_iter()
+            .map(|oid| self.commit_info(oid))
+            .collect()
+    }
 
-        let mut commits = Vec::new();
+    /// Commits for a `sync --from` value: `A..B` is (A, B] as in `git log`,
+    /// with an empty side meaning HEAD, and a single revision `A` is (A, HEAD]
+    pub fn get_revision_range(&self, spec: &str) -> anyhow::Result<Vec<CommitInfo>> {
+        if spec.contains("...") {
+            anyhow::bail!("Symmetric ranges ('{}') are not supported; use A..B", spec);
+        }
+        let (from, to) = spec.split_once("..").unwrap_or((spec, "HEAD"));
+        let from = if from.is_empty() { "HEAD" } else { from };
+        let to = if to.is_empty() { "HEAD" } else { to };
+        self.get_commit_range(from, to)
+    }
 
-        for oid in revwalk {
-            let oid = oid?;
-            let commit = self.repo.find_commit(oid)?;
-            let hash = oid.to_string();
-            let short_hash = hash[..7.min(hash.len())].to_string();
+    /// Resolve anything `git rev-parse` accepts (full or short hash, branch,
+    /// tag, `main~10`) to a commit
+    fn resolve_commit(&self, rev: &str) -> anyhow::Result<git2::Oid> {
+        let commit = self
+            .repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| {
+                format!(
+                    "Unknown revision '{}': expected a commit hash, branch, tag or an expression like HEAD~5",
+                    rev
+                )
+            })?;
+        Ok(commit.id())
+    }
 
-            commits.push(CommitInfo {
-                hash: hash.clone(),
-                short_hash,
-                message: commit.message().unwrap_or("").trim().to_string(),
-                author: commit.author().name().unwrap_or("Unknown").to_string(),
-                date: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
-                    .unwrap_or_else(|| chrono::Utc::now()),
-                parent_hashes: commit.parents().map(|p| p.id().to_string()).collect(),
-            });
+    pub fn get_diff(&self, commit_hash: &str) -> anyhow::Result<String> {
+        let (parent_tree, tree) = self.commit_trees(commit_hash)?;
+        let diff = self.diff_trees(parent_tree.as_ref(), &tree)?;
+
+        if !self.config.include_submodules {
+            return Ok(diff);
         }
+        // Put submodule summaries first so diff truncation never drops them
+        let summary = self.submodule_summary(parent_tree.as_ref(), &tree)?;
+        if summary.is_empty() {
+            Ok(diff)
+        } else {
+            Ok(format!("{}\n{}", summary, diff))
+        }
+    }
 
-        Ok(commits)
+    /// For each gitlink changed between the two trees, list the submodule
+    /// commits in `(old, new]`. Submodules that aren't checked out locally
+    /// only get their SHA range.
+    fn submodule_summary(&self, old: Option<&git2::Tree>, new: &git2::Tree) -> anyhow::Result<String> {
+        let diff = self.repo.diff_tree_to_tree(old, Some(new), None)?;
+        let submodules = self.repo.submodules()?;
+
+        let mut out = String::new();
+        for delta in diff.deltas() {
+            let (old_file, new_file) = (delta.old_file(), delta.new_file());
+            if old_file.mode() != FileMode::Commit && new_file.mode() != FileMode::Commit {
+                continue;
+            }
+            let Some(path) = new_file.path().or_else(|| old_file.path()) else {
+                continue;
+            };
+
+            let (old_id, new_id) = (old_file.id(), new_file.id());
+            out.push_str(&format!(
+                "Submodule {}: {}..{}",
+                path.display(),
+                short(&old_id),
+                short(&new_id)
+            ));
+
+            let sub_repo = submodules
+                .iter()
+                .find(|s| s.path() == path)
+                .and_then(|s| s.open().ok());
+            match sub_repo {
+                Some(sub) if !old_id.is_zero() && !new_id.is_zero() => {
+                    let mut revwalk = sub.revwalk()?;
+                    revwalk.set_sorting(Sort::TIME | Sort::TOPOLOGICAL)?;
+                    let commits: Vec<String> = match (revwalk.push(new_id), revwalk.hide(old_id)) {
+                        (Ok(()), Ok(())) => revwalk
+                            .filter_map(Result::ok)
+                            .filter_map(|oid| sub.find_commit(oid).ok())
+                            .map(|c| c.summary().unwrap_or("").to_string())
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    out.push_str(&format!(" ({} commit(s))\n", commits.len()));
+                    for message in commits.iter().take(SUBMODULE_COMMIT_LIMIT) {
+                        out.push_str(&format!("  - {}\n", message));
+                    }
+                    if commits.len() > SUBMODULE_COMMIT_LIMIT {
+                        out.push_str(&format!("  - ... and {} more\n", commits.len() - SUBMODULE_COMMIT_LIMIT));
+                    }
+                }
+                Some(_) if old_id.is_zero() => out.push_str(" (added)\n"),
+                Some(_) => out.push_str(" (removed)\n"),
+                None => out.push_str(" (not checked out)\n"),
+            }
+        }
+
+        Ok(out)
     }
 
-    pub fn get_diff(&self, commit_hash: &str) -> anyhow::Result<String> {
+    /// The touched files if every file the commit changes matches
+    /// `ignore_globs`, so there is nothing left to show the LLM
+    pub fn ignored_only_files(&self, commit_hash: &str) -> anyhow::Result<Option<Vec<String>>> {
+        if self.ignore_globs.is_empty() {
+            return Ok(None);
+        }
+        let (parent_tree, tree) = self.commit_trees(commit_hash)?;
+        let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            match delta.new_file().path().or_else(|| delta.old_file().path()) {
+                Some(path) if self.is_ignored(path) => files.push(path.display().to_string()),
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(files))
+    }
+
+    /// With `ignore_whitespace` on, returns the touched files if every change
+    /// in the commit is whitespace-only (no hunks survive the whitespace flags).
+    pub fn whitespace_only_files(&self, commit_hash: &str) -> anyhow::Result<Option<Vec<String>>> {
+        if !self.config.ignore_whitespace {
+            return Ok(None);
+        }
+        let (parent_tree, tree) = self.commit_trees(commit_hash)?;
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.ignore_whitespace(true).ignore_whitespace_change(true);
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
+
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+
+        let mut files = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            if delta.status() != Delta::Modified || delta.old_file().mode() != delta.new_file().mode() {
+                return Ok(None);
+            }
+            let hunks = git2::Patch::from_diff(&diff, idx)?
+                .map(|p| p.num_hunks())
+                .unwrap_or(1);
+            if hunks > 0 {
+                return Ok(None);
+            }
+            if let Some(path) = delta.new_file().path() {
+                files.push(path.display().to_string());
+            }
+        }
+        Ok(Some(files))
+    }
+
+    /// If every change in the commit is a file-mode flip or a symlink update,
+    /// returns a short canned summary and the affected paths. Such diffs have
+    /// no content lines, so the LLM has nothing meaningful to work with.
+    pub fn metadata_only_summary(
+        &self,
+        commit_hash: &str,
+    ) -> anyhow::Result<Option<(String, Vec<String>)>> {
+        let (parent_tree, tree) = self.commit_trees(commit_hash)?;
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+
+        let mut mode_changes = Vec::new();
+        let mut symlinks = Vec::new();
+        for delta in diff.deltas() {
+            let (old, new) = (delta.old_file(), delta.new_file());
+            let path = new
+                .path()
+                .or_else(|| old.path())
+                .map(|p| p.display().to_string())
+                .unwrap_or_default();
+
+            if old.mode() == FileMode::Link || new.mode() == FileMode::Link {
+                symlinks.push(path);
+            } else if delta.status() == Delta::Modified
+                && old.id() == new.id()
+                && old.mode() != new.mode()
+            {
+                mode_changes.push(path);
+            } else {
+                return Ok(None);
+            }
+        }
+
+        let mut parts = Vec::new();
+        if !mode_changes.is_empty() {
+            parts.push(format!("Changed file mode: {}", mode_changes.join(", ")));
+        }
+        if !symlinks.is_empty() {
+            parts.push(format!("Updated symlink: {}", symlinks.join(", ")));
+        }
+
+        let files = mode_changes.into_iter().chain(symlinks).collect();
+        Ok(Some((parts.join("; "), files)))
+    }
+
+    /// Top-level entries (directories suffixed with `/`) and the total number
+    /// of files in the commit's tree.
+    pub fn tree_overview(&self, commit_hash: &str) -> anyhow::Result<(Vec<String>, usize)> {
+        let (_, tree) = self.commit_trees(commit_hash)?;
+
+        let top_level = tree
+            .iter()
+            .map(|entry| {
+                let name = entry.name().unwrap_or("?").to_string();
+                if entry.kind() == Some(git2::ObjectType::Tree) {
+                    format!("{}/", name)
+                } else {
+                    name
+                }
+            })
+            .collect();
+
+        let mut file_count = 0;
+        tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                file_count += 1;
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+
+        Ok((top_level, file_count))
+    }
+
+    /// The commit's tree and its first parent's tree (`None` for a root commit)
+    fn commit_trees(&self, commit_hash: &str) -> anyhow::Result<(Option<git2::Tree<'_>>, git2::Tree<'_>)> {
         let oid = git2::Oid::from_str(commit_hash)?;
         let commit = self.repo.find_commit(oid)?;
 
@@ -102,15 +557,90 @@ impl GitAnalyzer {
             None
         };
 
+        Ok((parent_tree, tree))
+    }
+
+    /// Diff two arbitrary revisions (branch names, tags, hashes, `HEAD~3`, ...).
+    /// Unlike `get_diff`, `base` need not be a parent of `target`.
+    pub fn get_diff_between(&self, base: &str, target: &str) -> anyhow::Result<String> {
+        let base_tree = self.repo.revparse_single(base)?.peel_to_tree()?;
+        let target_tree = self.repo.revparse_single(target)?.peel_to_tree()?;
+        self.diff_trees(Some(&base_tree), &target_tree)
+    }
+
+    /// Lines added and removed by a commit against its first parent, with
+    /// the same options as `get_diff` and without files matching
+    /// `ignore_globs`, so the counts describe the diff the LLM sees
+    pub fn diff_stats(&self, commit_hash: &str) -> anyhow::Result<(usize, usize)> {
+        let (parent_tree, tree) = self.commit_trees(commit_hash)?;
+        let diff = self.repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&tree),
+            Some(&mut self.diff_options()),
+        )?;
+
+        let (mut insertions, mut deletions) = (0, 0);
+        for (idx, delta) in diff.deltas().enumerate() {
+            let path = delta.new_file().path().or_else(|| delta.old_file().path());
+            if path.is_some_and(|p| self.is_ignored(p)) {
+                continue;
+            }
+            if let Some(patch) = git2::Patch::from_diff(&diff, idx)? {
+                let (_, added, removed) = patch.line_stats()?;
+                insertions += added;
+                deletions += removed;
+            }
+        }
+        Ok((insertions, deletions))
+    }
+
+    fn diff_options(&self) -> DiffOptions {
         let mut diff_opts = DiffOptions::new();
         diff_opts.include_untracked(true);
+        diff_opts.context_lines(self.config.diff_context_lines);
+        if self.config.ignore_whitespace {
+            diff_opts.ignore_whitespace(true).ignore_whitespace_change(true);
+        }
+        diff_opts
+    }
 
-        let diff =
-            self.repo
-                .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
+    fn diff_trees(&self, old: Option<&git2::Tree>, new: &git2::Tree) -> anyhow::Result<String> {
+        let diff = self
+            .repo
+            .diff_tree_to_tree(old, Some(new), Some(&mut self.diff_options()))?;
+
+        // Buffer per file so a file with any non-UTF8 line can be replaced by
+        // a single marker instead of a diff with silently missing lines
+        struct FileDiff {
+            path: String,
+            header: String,
+            body: String,
+            non_utf8: bool,
+            binary: bool,
+        }
+        let mut files: Vec<FileDiff> = Vec::new();
+
+        diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
+            let path = delta.new_file().path().or_else(|| delta.old_file().path());
+            if path.is_some_and(|p| self.is_ignored(p)) {
+                return true;
+            }
+            let path = path.map(|p| p.display().to_string()).unwrap_or_default();
+            if files.last().map(|f| f.path != path).unwrap_or(true) {
+                files.push(FileDiff {
+                    path,
+                    header: String::new(),
+                    body: String::new(),
+                    non_utf8: false,
+                    binary: false,
+                });
+            }
+            let file = files.last_mut().expect("pushed above");
+            if line.origin() == 'B' || delta.flags().is_binary() {
+                file.binary = true;
+                return true;
+            }
 
-        let mut diff_text = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
             let prefix = match line.origin() {
                 '+' => "+",
                 '-' => "-",
@@ -118,13 +648,33 @@ impl GitAnalyzer {
                 'U' => "U",
                 _ => "",
             };
-            diff_text.push_str(prefix);
-            if let Ok(content) = std::str::from_utf8(line.content()) {
-                diff_text.push_str(content);
+            match std::str::from_utf8(line.content()) {
+                Ok(content) if line.origin() == 'F' => file.header.push_str(content),
+                Ok(content) => {
+                    file.body.push_str(prefix);
+                    file.body.push_str(content);
+                }
+                Err(_) => file.non_utf8 = true,
             }
             true
         })?;
 
+        let mut diff_text = String::new();
+        for file in files {
+            // Binary content means nothing to the LLM; say the file changed
+            if file.binary {
+                diff_text.push_str(&format!("{}{}\n", crate::core::context::BINARY_FILE_NOTE, file.path));
+                continue;
+            }
+            diff_text.push_str(&file.header);
+            if file.non_utf8 {
+                log::warn!("Non-UTF8 content in {} omitted from diff", file.path);
+                diff_text.push_str(&format!("[non-UTF8 content omitted: {}]\n", file.path));
+            } else {
+                diff_text.push_str(&file.body);
+            }
+        }
+
         Ok(diff_text)
     }
 
@@ -134,18 +684,76 @@ impl GitAnalyzer {
         Ok(revwalk.count())
     }
 
-    pub fn get_current_commit_hash(&self) -> anyhow::Result<String> {
-        let head = self.repo.head()?;
-        let oid = head.target().unwrap();
-        Ok(oid.to_string())
-    }
-
     pub fn get_hooks_path(&self) -> PathBuf {
         self.repo.path().join("hooks")
     }
 
-    #[allow(dead_code)]
     pub fn get_workdir(&self) -> Option<PathBuf> {
         self.repo.workdir().map(|p| p.to_path_buf())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::TestRepo;
+
+    #[cfg(unix)]
+    #[test]
+    fn mode_only_commit_gets_canned_summary() {
+        let repo = TestRepo::new();
+        repo.write("run.sh", "echo hi\n");
+        repo.commit("Add script");
+        repo.set_executable("run.sh", true);
+        let hash = repo.commit("Make script executable");
+
+        let git = GitAnalyzer::new(&repo.path()).unwrap();
+        let (summary, files) = git.metadata_only_summary(&hash).unwrap().expect("mode-only commit");
+        assert_eq!(summary, "Changed file mode: run.sh");
+        assert_eq!(files, vec!["run.sh".to_string()]);
+    }
+
+    #[test]
+    fn diff_stats_counts_lines_outside_ignored_files() {
+        let repo = TestRepo::new();
+        repo.write("src/lib.rs", "one\ntwo\nthree\n");
+        repo.write("Cargo.lock", "a\n");
+        repo.commit("Initial");
+        repo.write("src/lib.rs", "one\nTWO\nthree\nfour\nfive\n");
+        repo.write("Cargo.lock", "b\nc\nd\n");
+        let hash = repo.commit("Edit");
+
+        let git = GitAnalyzer::new(&repo.path()).unwrap();
+        assert_eq!(git.diff_stats(&hash).unwrap(), (6, 2));
+
+        let git = git.with_ignore_globs(&["Cargo.lock".to_string()]).unwrap();
+        assert_eq!(git.diff_stats(&hash).unwrap(), (3, 1));
+    }
+
+    #[test]
+    fn default_base_prefers_remote_head_then_local_branches() {
+        let repo = TestRepo::new();
+        repo.write("a.txt", "a\n");
+        let hash = repo.commit("Initial");
+
+        let raw = Repository::open(repo.path()).unwrap();
+        let oid = git2::Oid::from_str(&hash).unwrap();
+        raw.reference("refs/heads/feature", oid, true, "test").unwrap();
+        raw.set_head("refs/heads/feature").unwrap();
+        for name in ["refs/heads/main", "refs/heads/master"] {
+            if let Ok(mut reference) = raw.find_reference(name) {
+                reference.delete().unwrap();
+            }
+        }
+        let git = GitAnalyzer::new(&repo.path()).unwrap();
+        assert!(git.default_base().is_err());
+
+        raw.reference("refs/heads/master", oid, true, "test").unwrap();
+        assert_eq!(git.default_base().unwrap(), "master");
+
+        raw.reference("refs/remotes/origin/trunk", oid, true, "test").unwrap();
+        raw.reference_symbolic("refs/remotes/origin/HEAD", "refs/remotes/origin/trunk", true, "test")
+            .unwrap();
+        assert_eq!(git.default_base().unwrap(), "origin/trunk");
+    }
+}
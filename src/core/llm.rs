@@ -1,13 +1,69 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::core::cache::ResponseCache;
 use crate::utils::config::OllamaConfig;
+use crate::utils::error::ContextHubError;
+
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Bump whenever `DEFAULT_PROMPT_TEMPLATE` changes meaningfully, so entries
+/// generated with an older prompt can be found and regenerated
+/// (`sync --refresh-stale`). Entries from a custom `.contexthub/prompt.md`
+/// get a hash of the template appended (see `prompt_version`).
+pub const PROMPT_VERSION: &str = "1";
+
+/// The version stamped on entries built from `template`: `PROMPT_VERSION`
+/// for the built-in prompt, or `PROMPT_VERSION` plus the first 8 hex digits
+/// of the template's SHA-256 (e.g. `1+3f9a0c2e`), so editing the template
+/// makes existing entries stale
+pub fn prompt_version(template: Option<&str>) -> String {
+    match template {
+        None => PROMPT_VERSION.to_string(),
+        Some(template) => {
+            let digest = Sha256::digest(template.as_bytes());
+            let hash: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+            format!("{}+{}", PROMPT_VERSION, hash)
+        }
+    }
+}
+
+/// The extraction prompt used when `.contexthub/prompt.md` doesn't exist.
+/// `{guidance}` and `{previous_context}` expand to whole sections, headings
+/// included, or to nothing; the other placeholders to the bare values.
+pub const DEFAULT_PROMPT_TEMPLATE: &str = r#"You are a code context analyzer. Given a git commit diff, extract structured information about what was changed.
+{guidance}{previous_context}
+Commit Message: {commit_message}
+
+Files Changed: {files_changed}
+
+Diff:
+{diff}
+
+Respond ONLY with valid JSON (no other text):
+{
+  "summary": "1-2 sentence description of what this commit does",
+  "files_changed": ["list of key files that were modified"],
+  "key_details": ["2-4 important technical details about this change"],
+  "technologies": ["technologies/libraries used"],
+  "impact": "high|medium|low - how significant is this change"
+}"#;
+
+/// Placeholders substituted in a prompt template
+pub const PROMPT_PLACEHOLDERS: [&str; 5] =
+    ["commit_message", "files_changed", "diff", "previous_context", "guidance"];
 
 #[derive(Debug, Serialize)]
 struct OllamaRequest {
     model: String,
     prompt: String,
     stream: bool,
+    /// `"json"` constrains generation to valid JSON (`ollama.force_json`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
     options: OllamaOptions,
 }
 
@@ -15,6 +71,12 @@ struct OllamaRequest {
 struct OllamaOptions {
     temperature: f32,
     num_predict: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repeat_penalty: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +84,155 @@ struct OllamaResponse {
     response: String,
 }
 
+#[derive(Debug, Serialize)]
+struct OllamaEmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+/// One line of a streamed `/api/generate` response
+#[derive(Debug, Deserialize)]
+struct OllamaStreamChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 1],
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    max_tokens: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatReply,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatReply {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// The JSON object the extraction prompt asks for; only `summary` is required
+#[derive(Debug, Deserialize)]
+struct RawContext {
+    summary: String,
+    #[serde(default)]
+    files_changed: Vec<String>,
+    #[serde(default)]
+    key_details: Vec<String>,
+    #[serde(default)]
+    technologies: Vec<String>,
+    #[serde(default)]
+    impact: String,
+}
+
+impl From<RawContext> for ExtractedContext {
+    fn from(raw: RawContext) -> Self {
+        Self {
+            summary: raw.summary,
+            files_changed: raw.files_changed,
+            key_details: raw.key_details,
+            technologies: raw.technologies,
+            impact: if raw.impact.is_empty() { "medium".to_string() } else { raw.impact },
+        }
+    }
+}
+
+/// Ollama answered, but with nothing (or only whitespace), even after a retry.
+#[derive(Debug)]
+pub struct EmptyLlmResponse;
+
+impl std::fmt::Display for EmptyLlmResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ollama returned an empty response")
+    }
+}
+
+impl std::error::Error for EmptyLlmResponse {}
+
+/// Attempts per prompt when Ollama returns an empty response
+const EMPTY_RESPONSE_ATTEMPTS: usize = 2;
+
+/// Wait before the first retry of a failed request; doubled for each further one
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// The LLM server answered with an error status
+#[derive(Debug)]
+pub struct LlmHttpError {
+    pub status: reqwest::StatusCode,
+    message: String,
+}
+
+impl LlmHttpError {
+    fn error(status: reqwest::StatusCode, message: String) -> anyhow::Error {
+        Self { status, message }.into()
+    }
+}
+
+impl std::fmt::Display for LlmHttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for LlmHttpError {}
+
+/// Whether a failed request is worth repeating: timeouts, connection errors,
+/// 5xx and 429 are; other HTTP errors (bad model, bad key) are not.
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_timeout() || e.is_connect() || e.is_request() || e.is_body();
+        }
+        if let Some(e) = cause.downcast_ref::<LlmHttpError>() {
+            return e.status.is_server_error() || e.status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        }
+        false
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedContext {
     pub summary: String,
@@ -31,57 +242,104 @@ pub struct ExtractedContext {
     pub impact: String,
 }
 
-pub struct LlmProcessor {
+/// An API the extraction prompt can be sent to, selected by `ollama.provider`
+pub trait LlmBackend {
+    /// Whether the server answers, bounded by `HEALTH_CHECK_TIMEOUT`
+    async fn is_available(&self, config: &OllamaConfig) -> bool;
+
+    /// Send one prompt and return the raw completion text
+    async fn complete(&self, config: &OllamaConfig, prompt: &str, max_tokens: usize) -> anyhow::Result<String>;
+
+    /// Names of the models the server offers
+    async fn list_models(&self, config: &OllamaConfig) -> anyhow::Result<Vec<String>>;
+
+    /// Embedding vector of `text` from `ollama.embedding_model`
+    async fn embed(&self, config: &OllamaConfig, text: &str) -> anyhow::Result<Vec<f32>>;
+}
+
+/// Ollama's native `/api/generate` and `/api/tags`
+pub struct OllamaBackend {
     client: Client,
-    config: OllamaConfig,
 }
 
-impl LlmProcessor {
-    pub fn new(config: OllamaConfig) -> Self {
-        Self {
-            client: Client::new(),
-            config,
+/// Any server with OpenAI's `/chat/completions` and `/models`; `endpoint` is
+/// the base URL including the version, e.g. `http://localhost:1234/v1`
+pub struct OpenAiBackend {
+    client: Client,
+    api_key: Option<String>,
+}
+
+/// The configured backend
+pub enum Backend {
+    Ollama(OllamaBackend),
+    OpenAi(OpenAiBackend),
+}
+
+impl Backend {
+    pub fn new(config: &OllamaConfig) -> anyhow::Result<Self> {
+        let client = build_client(config)?;
+        Ok(match config.provider.as_str() {
+            "openai" => Self::OpenAi(OpenAiBackend { client, api_key: config.resolved_api_key() }),
+            _ => Self::Ollama(OllamaBackend { client }),
+        })
+    }
+}
+
+impl LlmBackend for Backend {
+    async fn is_available(&self, config: &OllamaConfig) -> bool {
+        match self {
+            Self::Ollama(backend) => backend.is_available(config).await,
+            Self::OpenAi(backend) => backend.is_available(config).await,
         }
     }
 
-    pub fn is_ollama_running(&self) -> bool {
-        // Use a blocking reqwest call instead of shelling out to curl
-        let url = format!("{}/api/tags", self.config.endpoint);
-        reqwest::blocking::get(&url)
-            .map(|resp| resp.status().is_success())
-            .unwrap_or(false)
+    async fn complete(&self, config: &OllamaConfig, prompt: &str, max_tokens: usize) -> anyhow::Result<String> {
+        match self {
+            Self::Ollama(backend) => backend.complete(config, prompt, max_tokens).await,
+            Self::OpenAi(backend) => backend.complete(config, prompt, max_tokens).await,
+        }
     }
 
-    #[allow(dead_code)]
-    pub async fn check_ollama(&self) -> anyhow::Result<bool> {
-        let url = format!("{}/api/tags", self.config.endpoint);
-        match self.client.get(&url).send().await {
-            Ok(resp) => Ok(resp.status().is_success()),
-            Err(_) => Ok(false),
+    async fn list_models(&self, config: &OllamaConfig) -> anyhow::Result<Vec<String>> {
+        match self {
+            Self::Ollama(backend) => backend.list_models(config).await,
+            Self::OpenAi(backend) => backend.list_models(config).await,
         }
     }
 
-    pub async fn extract_context(
-        &self,
-        commit_message: &str,
-        diff: &str,
-        files_changed: &[String],
-        previous_context: Option<&str>,
-    ) -> anyhow::Result<ExtractedContext> {
-        let prompt = Self::build_prompt(commit_message, diff, files_changed, previous_context);
-        
+    async fn embed(&self, config: &OllamaConfig, text: &str) -> anyhow::Result<Vec<f32>> {
+        match self {
+            Self::Ollama(backend) => backend.embed(config, text).await,
+            Self::OpenAi(backend) => backend.embed(config, text).await,
+        }
+    }
+}
+
+impl LlmBackend for OllamaBackend {
+    async fn is_available(&self, config: &OllamaConfig) -> bool {
+        let url = format!("{}/api/tags", config.endpoint);
+        match self.client.get(&url).timeout(HEALTH_CHECK_TIMEOUT).send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    async fn complete(&self, config: &OllamaConfig, prompt: &str, max_tokens: usize) -> anyhow::Result<String> {
+        let url = format!("{}/api/generate", config.endpoint);
         let request = OllamaRequest {
-            model: self.config.model.clone(),
-            prompt,
-            stream: false,
+            model: config.model.clone(),
+            prompt: prompt.to_string(),
+            stream: config.stream,
+            format: config.force_json.then_some("json"),
             options: OllamaOptions {
-                temperature: self.config.temperature,
-                num_predict: self.config.max_tokens,
+                temperature: config.temperature,
+                num_predict: max_tokens,
+                num_ctx: config.num_ctx,
+                top_p: config.top_p,
+                repeat_penalty: config.repeat_penalty,
             },
         };
 
-        let url = format!("{}/api/generate", self.config.endpoint);
-        
         let response = self.client
             .post(&url)
             .json(&request)
@@ -91,100 +349,496 @@ impl LlmProcessor {
         let status = response.status();
         if !status.is_success() {
             if status == reqwest::StatusCode::NOT_FOUND {
-                return Err(anyhow::anyhow!(
+                return Err(LlmHttpError::error(status, format!(
                     "Model '{}' not found. Pull it first with:\n  ollama pull {}\nOr change model: contexthub config set-model <model>",
-                    self.config.model, self.config.model
-                ));
+                    config.model, config.model
+                )));
             }
-            return Err(anyhow::anyhow!("Ollama returned error: {}", status));
+            return Err(LlmHttpError::error(status, format!("Ollama returned error: {}", status)));
+        }
+
+        if config.stream {
+            let text = read_stream(response).await?;
+            log::debug!("LLM raw response:\n{}", text);
+            return Ok(text);
         }
 
         let ollama_resp: OllamaResponse = response.json().await?;
+        log::debug!("LLM raw response:\n{}", ollama_resp.response);
         
-        Self::parse_response(&ollama_resp.response)
+        Ok(ollama_resp.response)
+    }
+
+    async fn list_models(&self, config: &OllamaConfig) -> anyhow::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct TagsResponse {
+            models: Vec<ModelEntry>,
+        }
+        #[derive(Deserialize)]
+        struct ModelEntry {
+            name: String,
+        }
+
+        let url = format!("{}/api/tags", config.endpoint);
+        let resp = self.client
+            .get(&url)
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Ollama returned {}", resp.status());
+        }
+        let tags: TagsResponse = resp.json().await?;
+        Ok(tags.models.into_iter().map(|m| m.name).collect())
+    }
+
+    async fn embed(&self, config: &OllamaConfig, text: &str) -> anyhow::Result<Vec<f32>> {
+        let url = format!("{}/api/embeddings", config.endpoint);
+        let request = OllamaEmbeddingRequest {
+            model: &config.embedding_model,
+            prompt: text,
+        };
+        let response = self.client.post(&url).json(&request).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(LlmHttpError::error(status, format!(
+                    "Embedding model '{}' not found. Pull it first with:\n  ollama pull {}",
+                    config.embedding_model, config.embedding_model
+                )));
+            }
+            return Err(LlmHttpError::error(status, format!("Ollama returned error: {}", status)));
+        }
+
+        let embedding: OllamaEmbeddingResponse = response.json().await?;
+        if embedding.embedding.is_empty() {
+            anyhow::bail!("'{}' returned an empty embedding; is it an embedding model?", config.embedding_model);
+        }
+        Ok(embedding.embedding)
+    }
+}
+
+/// Accumulate the `response` fragments of a streamed `/api/generate` reply
+/// (newline-delimited JSON), showing a spinner and token count on stderr
+/// when it is a terminal.
+async fn read_stream(mut response: reqwest::Response) -> anyhow::Result<String> {
+    use std::io::{IsTerminal, Write};
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+    let progress = std::io::stderr().is_terminal();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut text = String::new();
+    let mut tokens = 0usize;
+    let mut done = false;
+
+    while !done {
+        let Some(bytes) = response.chunk().await? else {
+            // A final line without a trailing newline
+            pending.push(b'\n');
+            done = true;
+            continue;
+        };
+        pending.extend_from_slice(&bytes);
+
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let chunk: OllamaStreamChunk = serde_json::from_slice(&line)?;
+            if let Some(error) = chunk.error {
+                if progress {
+                    eprint!("\r\x1b[2K");
+                }
+                anyhow::bail!("Ollama returned error: {}", error);
+            }
+            text.push_str(&chunk.response);
+            tokens += 1;
+            if progress {
+                eprint!("\r  {} receiving… {} tokens", FRAMES[tokens % FRAMES.len()], tokens);
+                let _ = std::io::stderr().flush();
+            }
+            if chunk.done {
+                done = true;
+                break;
+            }
+        }
+    }
+
+    if progress {
+        eprint!("\r\x1b[2K");
+    }
+    Ok(text)
+}
+
+impl OpenAiBackend {
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.client.get(url))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+}
+
+impl LlmBackend for OpenAiBackend {
+    async fn is_available(&self, config: &OllamaConfig) -> bool {
+        let url = format!("{}/models", config.endpoint.trim_end_matches('/'));
+        match self.get(&url).timeout(HEALTH_CHECK_TIMEOUT).send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    async fn complete(&self, config: &OllamaConfig, prompt: &str, max_tokens: usize) -> anyhow::Result<String> {
+        let url = format!("{}/chat/completions", config.endpoint.trim_end_matches('/'));
+        let request = ChatRequest {
+            model: &config.model,
+            messages: [ChatMessage { role: "user", content: prompt }],
+            temperature: config.temperature,
+            top_p: config.top_p,
+            max_tokens,
+        };
+
+        let response = self.authorize(self.client.post(&url)).json(&request).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let detail = body.trim();
+            let message = match status {
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => format!(
+                    "{} rejected the API key ({}). Set CONTEXTHUB_API_KEY or ollama.api_key",
+                    config.endpoint, status
+                ),
+                reqwest::StatusCode::NOT_FOUND => format!(
+                    "{} returned 404 for model '{}'. Check ollama.endpoint (it should end in the API version, e.g. /v1) and the model name",
+                    config.endpoint, config.model
+                ),
+                _ if detail.is_empty() => format!("LLM API returned error: {}", status),
+                _ => format!("LLM API returned error: {}: {}", status, &detail[..detail.len().min(300)]),
+            };
+            return Err(LlmHttpError::error(status, message));
+        }
+
+        let chat: ChatResponse = response.json().await?;
+        let content = chat
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .unwrap_or_default();
+        log::debug!("LLM raw response:\n{}", content);
+
+        Ok(content)
+    }
+
+    async fn list_models(&self, config: &OllamaConfig) -> anyhow::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct ModelsResponse {
+            data: Vec<ModelEntry>,
+        }
+        #[derive(Deserialize)]
+        struct ModelEntry {
+            id: String,
+        }
+
+        let url = format!("{}/models", config.endpoint.trim_end_matches('/'));
+        let resp = self.get(&url).timeout(HEALTH_CHECK_TIMEOUT).send().await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("LLM API returned {}", resp.status());
+        }
+        let models: ModelsResponse = resp.json().await?;
+        Ok(models.data.into_iter().map(|m| m.id).collect())
+    }
+
+    async fn embed(&self, config: &OllamaConfig, text: &str) -> anyhow::Result<Vec<f32>> {
+        let url = format!("{}/embeddings", config.endpoint.trim_end_matches('/'));
+        let request = EmbeddingRequest {
+            model: &config.embedding_model,
+            input: text,
+        };
+        let response = self.authorize(self.client.post(&url)).json(&request).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(LlmHttpError::error(status, format!(
+                "LLM API returned error for embedding model '{}': {}",
+                config.embedding_model, status
+            )));
+        }
+
+        let embedding: EmbeddingResponse = response.json().await?;
+        match embedding.data.into_iter().next() {
+            Some(data) if !data.embedding.is_empty() => Ok(data.embedding),
+            _ => anyhow::bail!("'{}' returned an empty embedding", config.embedding_model),
+        }
+    }
+}
+
+pub struct LlmProcessor {
+    backend: Backend,
+    config: OllamaConfig,
+    guidance: Option<String>,
+    template: Option<String>,
+    prompt_version: String,
+    cache: Option<ResponseCache>,
+}
+
+impl LlmProcessor {
+    pub fn new(config: OllamaConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend: Backend::new(&config)?,
+            config,
+            guidance: None,
+            template: None,
+            prompt_version: PROMPT_VERSION.to_string(),
+            cache: None,
+        })
+    }
+
+    /// Look up and store extraction results in `cache`
+    pub fn with_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Extra project instructions injected into every prompt (see `load_prompt_guidance`)
+    pub fn with_guidance(mut self, guidance: Option<String>) -> Self {
+        self.guidance = guidance;
+        self
+    }
+
+    /// Build prompts from `template` instead of `DEFAULT_PROMPT_TEMPLATE`
+    /// (see `load_prompt_template`)
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.prompt_version = prompt_version(template.as_deref());
+        self.template = template;
+        self
+    }
+
+    /// Version of the prompt in use (see `prompt_version`)
+    pub fn prompt_version(&self) -> &str {
+        &self.prompt_version
+    }
+
+    /// Non-blocking health check of the configured backend. Bounded by
+    /// `HEALTH_CHECK_TIMEOUT` so a wedged server can't hang the caller.
+    pub async fn is_ollama_running(&self) -> bool {
+        self.backend.is_available(&self.config).await
+    }
+
+    pub async fn extract_context(
+        &self,
+        commit_message: &str,
+        diff: &str,
+        files_changed: &[String],
+        previous_context: Option<&str>,
+    ) -> anyhow::Result<ExtractedContext> {
+        let num_predict = self.config.extraction_max_tokens();
+        self.generate(commit_message, diff, files_changed, previous_context, num_predict)
+            .await
+    }
+
+    /// Like `extract_context`, but for summaries spanning many commits, which
+    /// get the larger overview response budget.
+    pub async fn extract_overview(
+        &self,
+        message: &str,
+        diff: &str,
+        files_changed: &[String],
+    ) -> anyhow::Result<ExtractedContext> {
+        let num_predict = self.config.overview_max_tokens();
+        self.generate(message, diff, files_changed, None, num_predict).await
+    }
+
+    /// Rough token count (chars / 4) of the full extraction prompt
+    pub fn estimate_prompt_tokens(
+        &self,
+        commit_message: &str,
+        diff: &str,
+        files_changed: &[String],
+        previous_context: Option<&str>,
+    ) -> usize {
+        self.build_prompt(commit_message, diff, files_changed, previous_context).len() / 4
+    }
+
+    async fn generate(
+        &self,
+        commit_message: &str,
+        diff: &str,
+        files_changed: &[String],
+        previous_context: Option<&str>,
+        num_predict: usize,
+    ) -> anyhow::Result<ExtractedContext> {
+        let prompt = self.build_prompt(commit_message, diff, files_changed, previous_context);
+        log::debug!("LLM prompt ({} chars, num_predict {}):\n{}", prompt.len(), num_predict, prompt);
+
+        // Everything that shapes the answer is part of the key. Sampling
+        // options are only added when set, so existing keys stay valid.
+        let temperature = self.config.temperature.to_string();
+        let num_predict_part = num_predict.to_string();
+        let top_p = self.config.top_p.map(|v| format!("top_p={}", v));
+        let repeat_penalty = self.config.repeat_penalty.map(|v| format!("repeat_penalty={}", v));
+        let num_ctx = self.config.num_ctx.map(|v| format!("num_ctx={}", v));
+        let mut key_parts = vec![
+            self.config.provider.as_str(),
+            &self.config.model,
+            &temperature,
+            &num_predict_part,
+            &prompt,
+        ];
+        key_parts.extend(top_p.as_deref());
+        key_parts.extend(repeat_penalty.as_deref());
+        key_parts.extend(num_ctx.as_deref());
+        let cache_key = ResponseCache::key(&key_parts);
+        if let Some(context) = self.cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+            log::info!("Using cached LLM result {}", cache_key);
+            return Ok(context);
+        }
+
+        for attempt in 1..=EMPTY_RESPONSE_ATTEMPTS {
+            let response = self.complete_with_retry(&prompt, num_predict).await?;
+            if !response.trim().is_empty() {
+                // A response that isn't the requested JSON may parse next
+                // time, so only structured results are cached
+                let Some(context) = Self::parse_response(&response) else {
+                    return Ok(Self::raw_response_context(&response));
+                };
+                if let Some(cache) = &self.cache {
+                    if let Err(e) = cache.put(&cache_key, &context) {
+                        log::warn!("Could not cache LLM result: {}", e);
+                    }
+                }
+                return Ok(context);
+            }
+            log::warn!(
+                "Empty response from {} (attempt {}/{})",
+                self.config.model,
+                attempt,
+                EMPTY_RESPONSE_ATTEMPTS
+            );
+        }
+
+        Err(EmptyLlmResponse.into())
+    }
+
+    /// `complete`, repeated up to `max_retries` times with exponential
+    /// backoff while the failure is transient (see `is_transient`)
+    async fn complete_with_retry(&self, prompt: &str, max_tokens: usize) -> anyhow::Result<String> {
+        let attempts = self.config.max_retries + 1;
+        let mut delay = RETRY_BASE_DELAY;
+        for attempt in 1..=attempts {
+            match self.backend.complete(&self.config, prompt, max_tokens).await {
+                Ok(response) => return Ok(response),
+                Err(e) if !is_transient(&e) => return Err(e),
+                Err(e) if attempt == attempts => {
+                    let reason = self.failure_reason(&e);
+                    return Err(e.context(format!(
+                        "LLM request to {} failed after {} attempt(s): {}",
+                        self.config.endpoint, attempts, reason
+                    )));
+                }
+                Err(e) => {
+                    log::warn!(
+                        "LLM request failed (attempt {}/{}): {}; retrying in {}s",
+                        attempt,
+                        attempts,
+                        self.failure_reason(&e),
+                        delay.as_secs()
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+
+    /// Embedding of `text` for semantic search. Not cached or retried: a
+    /// missing vector is filled in lazily by the next `search --semantic`.
+    pub async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        if self.config.embedding_model.is_empty() {
+            anyhow::bail!("Embeddings are disabled (ollama.embedding_model is empty)");
+        }
+        self.backend.embed(&self.config, text).await
+    }
+
+    /// Short description of a transient failure for logs and the final error
+    fn failure_reason(&self, err: &anyhow::Error) -> String {
+        match err.chain().find_map(|cause| cause.downcast_ref::<reqwest::Error>()) {
+            Some(e) if e.is_timeout() => format!("timed out after {}s", self.config.timeout_secs),
+            Some(e) if e.is_connect() => "could not connect".to_string(),
+            _ => err.to_string(),
+        }
     }
 
     fn build_prompt(
+        &self,
         commit_message: &str,
         diff: &str,
         files_changed: &[String],
         previous_context: Option<&str>,
     ) -> String {
+        let guidance_section = match &self.guidance {
+            Some(text) => format!("\nProject guidance:\n{}\n", text),
+            None => String::new(),
+        };
         let prev_section = match previous_context {
             Some(ctx) => format!(
-                "\nPrevious Context (from the last processed commit):\n{}\n\nUse this to understand the evolving codebase and build incremental knowledge.\n",
+                "\nPrevious Context (from the most recently processed commits, oldest first):\n{}\n\nUse this to understand the evolving codebase and build incremental knowledge.\n",
                 ctx
             ),
             None => String::new(),
         };
 
-        format!(r#"You are a code context analyzer. Given a git commit diff, extract structured information about what was changed.
-{}
-Commit Message: {}
-
-Files Changed: {}
-
-Diff:
-{}
-
-Respond ONLY with valid JSON (no other text):
-{{
-  "summary": "1-2 sentence description of what this commit does",
-  "files_changed": ["list of key files that were modified"],
-  "key_details": ["2-4 important technical details about this change"],
-  "technologies": ["technologies/libraries used"],
-  "impact": "high|medium|low - how significant is this change"
-}}"#, prev_section, commit_message, files_changed.join(", "), diff)
+        fill_template(
+            self.template.as_deref().unwrap_or(DEFAULT_PROMPT_TEMPLATE),
+            &[
+                ("commit_message", commit_message),
+                ("files_changed", &files_changed.join(", ")),
+                ("diff", diff),
+                ("previous_context", &prev_section),
+                ("guidance", &guidance_section),
+            ],
+        )
     }
 
-    fn parse_response(response: &str) -> anyhow::Result<ExtractedContext> {
-        if response.is_empty() {
-            return Ok(ExtractedContext {
-                summary: "Empty response from LLM".to_string(),
-                files_changed: vec![],
-                key_details: vec![],
-                technologies: vec![],
-                impact: "low".to_string(),
-            });
+    /// The response as JSON, which is all it should be with `force_json`.
+    /// Otherwise models tend to wrap it in markdown fences or commentary, so
+    /// the outermost `{...}` is tried next. `None` if neither parses.
+    fn parse_response(response: &str) -> Option<ExtractedContext> {
+        if let Ok(raw) = serde_json::from_str::<RawContext>(response.trim()) {
+            return Some(raw.into());
         }
-        
+
         let json_start = response.find('{');
         let json_end = response.rfind('}');
-        
+
         if let (Some(start), Some(end)) = (json_start, json_end) {
-            let json_str = &response[start..=end];
-            
-            #[derive(Deserialize)]
-            struct RawContext {
-                summary: String,
-                #[serde(default)]
-                files_changed: Vec<String>,
-                #[serde(default)]
-                key_details: Vec<String>,
-                #[serde(default)]
-                technologies: Vec<String>,
-                #[serde(default)]
-                impact: String,
-            }
-            
-            if let Ok(raw) = serde_json::from_str::<RawContext>(json_str) {
-                return Ok(ExtractedContext {
-                    summary: raw.summary,
-                    files_changed: raw.files_changed,
-                    key_details: raw.key_details,
-                    technologies: raw.technologies,
-                    impact: if raw.impact.is_empty() { "medium".to_string() } else { raw.impact },
-                });
+            if let Ok(raw) = serde_json::from_str::<RawContext>(&response[start..=end]) {
+                log::debug!("Extracted JSON from surrounding text in the LLM response");
+                return Some(raw.into());
             }
         }
-        
-        Ok(ExtractedContext {
+        None
+    }
+
+    /// Last resort for a response `parse_response` can't read: the raw text
+    /// becomes the summary
+    fn raw_response_context(response: &str) -> ExtractedContext {
+        log::warn!("LLM response is not the requested JSON; storing it as the summary");
+        ExtractedContext {
             summary: format!("Raw LLM response: {}", &response[..response.len().min(200)]),
             files_changed: vec![],
             key_details: vec![],
             technologies: vec![],
             impact: "low".to_string(),
-        })
+        }
     }
 
     #[allow(dead_code)]
@@ -209,25 +863,131 @@ Respond ONLY with valid JSON (no other text):
     }
 }
 
-/// Fetch the list of locally available models from a running Ollama instance.
-/// Returns model names (e.g. ["llama3.2:latest", "mistral:latest"]).
-pub fn fetch_available_models(endpoint: &str) -> anyhow::Result<Vec<String>> {
-    #[derive(Deserialize)]
-    struct TagsResponse {
-        models: Vec<ModelEntry>,
+/// Replace each `{name}` in `template` with its value in one pass, so a value
+/// containing placeholder text (a diff of this very template, say) is left
+/// alone. Other braces, like those of the JSON example, are kept as they are.
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len() + values.iter().map(|(_, v)| v.len()).sum::<usize>());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start + 1..];
+        let placeholder = values.iter().find(|(name, _)| {
+            tail.strip_prefix(name).is_some_and(|after| after.starts_with('}'))
+        });
+        match placeholder {
+            Some((name, value)) => {
+                out.push_str(value);
+                rest = &tail[name.len() + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = tail;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The prompt template in `path` (e.g. `.contexthub/prompt.md`), or `None`
+/// when the file doesn't exist or is blank
+pub fn load_prompt_template(path: &PathBuf) -> anyhow::Result<Option<String>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow::Error::new(e).context(format!("Could not read {}", path.display()))),
+    };
+    let template = text.trim_end();
+    if template.trim().is_empty() {
+        return Ok(None);
+    }
+    if !template.contains("{diff}") {
+        log::warn!("{} has no {{diff}} placeholder; the model won't see the changes", path.display());
+    }
+    Ok(Some(template.to_string()))
+}
+
+/// Concatenate the `.txt` fragments in `dir` (e.g. `.contexthub/prompt.d/`)
+/// in lexical file-name order. A missing or empty directory yields `None`.
+pub fn load_prompt_guidance(dir: &PathBuf) -> anyhow::Result<Option<String>> {
+    if !dir.is_dir() {
+        return Ok(None);
     }
-    #[derive(Deserialize)]
-    struct ModelEntry {
-        name: String,
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+
+    let mut fragments = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path)?;
+        if !text.trim().is_empty() {
+            fragments.push(text.trim().to_string());
+        }
     }
 
-    let url = format!("{}/api/tags", endpoint);
-    let resp = reqwest::blocking::get(&url)?;
-    if !resp.status().is_success() {
-        anyhow::bail!("Ollama returned {}", resp.status());
+    if fragments.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(fragments.join("\n\n")))
     }
-    let tags: TagsResponse = resp.json()?;
-    Ok(tags.models.into_iter().map(|m| m.name).collect())
+}
+
+/// HTTP client honoring the configured proxy and TLS settings. Without an
+/// explicit `proxy_url`, reqwest picks up `HTTP_PROXY`/`HTTPS_PROXY`.
+fn build_client(config: &OllamaConfig) -> anyhow::Result<Client> {
+    let mut builder = Client::builder();
+
+    if config.timeout_secs > 0 {
+        let timeout = Duration::from_secs(config.timeout_secs);
+        // A streamed answer may take longer overall; bound the silence instead
+        builder = if config.stream {
+            builder.read_timeout(timeout)
+        } else {
+            builder.timeout(timeout)
+        };
+    }
+
+    if let Some(url) = &config.proxy_url {
+        let proxy = reqwest::Proxy::all(url).map_err(|e| {
+            ContextHubError::Config(format!("Invalid ollama.proxy_url '{}': {}", url, e))
+        })?;
+        builder = builder.proxy(proxy);
+    }
+
+    if config.accept_invalid_certs {
+        log::warn!("TLS certificate verification is disabled (ollama.accept_invalid_certs)");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Fetch the list of models the configured backend offers.
+/// Returns model names (e.g. ["llama3.2:latest", "mistral:latest"]).
+pub async fn fetch_available_models(config: &OllamaConfig) -> anyhow::Result<Vec<String>> {
+    Backend::new(config)?.list_models(config).await
+}
+
+/// Code-tuned model families, best first. Matched against the name before the tag.
+const CODE_MODELS: [&str; 3] = ["qwen2.5-coder", "deepseek-coder", "codellama"];
+
+/// Index of the model `init` should pre-select: the best code-capable model
+/// if one is installed, otherwise the first that isn't an embedding model.
+pub fn recommended_model(models: &[String]) -> usize {
+    let family = |name: &str| {
+        let base = name.rsplit('/').next().unwrap_or(name);
+        base.split(':').next().unwrap_or(base).to_lowercase()
+    };
+
+    CODE_MODELS
+        .iter()
+        .find_map(|code| models.iter().position(|m| family(m).starts_with(code)))
+        .or_else(|| models.iter().position(|m| !family(m).contains("embed")))
+        .unwrap_or(0)
 }
 
 pub fn check_ollama_installation() -> bool {
@@ -237,3 +997,26 @@ pub fn check_ollama_installation() -> bool {
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_template_changes_prompt_version() {
+        assert_eq!(prompt_version(None), PROMPT_VERSION);
+
+        let custom = prompt_version(Some("Summarize:\n{diff}"));
+        assert!(custom.starts_with(&format!("{}+", PROMPT_VERSION)));
+        assert_eq!(custom.len(), PROMPT_VERSION.len() + 9);
+        assert_eq!(custom, prompt_version(Some("Summarize:\n{diff}")));
+        assert_ne!(custom, prompt_version(Some("Describe:\n{diff}")));
+    }
+
+    #[test]
+    fn parse_response_rejects_prose() {
+        let fenced = "```json\n{\"summary\": \"Add parser\"}\n```";
+        assert_eq!(LlmProcessor::parse_response(fenced).unwrap().summary, "Add parser");
+        assert!(LlmProcessor::parse_response("I cannot do that.").is_none());
+    }
+}
@@ -2,3 +2,6 @@ pub mod git;
 pub mod llm;
 pub mod storage;
 pub mod context;
+pub mod cache;
+#[cfg(test)]
+pub mod test_support;
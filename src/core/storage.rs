@@ -1,9 +1,12 @@
 use chrono::{DateTime, Duration, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, ErrorCode, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::core::git::CommitInfo;
+use crate::core::llm::ExtractedContext;
+use crate::utils::config::global_db_path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalContext {
@@ -15,6 +18,90 @@ pub struct GlobalContext {
     pub files_changed: String,
     pub llm_extracted_context: String,
     pub created_at: DateTime<Utc>,
+    /// Model that generated the entry (`None` for entries predating this column)
+    pub model: Option<String>,
+    pub prompt_version: Option<String>,
+    pub author: Option<String>,
+    /// Set when the LLM gave no usable answer; such entries are kept out of
+    /// listings, exports and chaining
+    pub extraction_failed: bool,
+    /// Branch checked out when the entry was processed (`None` for detached
+    /// HEAD and entries predating this column)
+    pub branch: Option<String>,
+    /// Lines added and removed by the commit (`None` for entries predating these columns)
+    pub insertions: Option<usize>,
+    pub deletions: Option<usize>,
+    /// `context_summary` was corrected with `context edit`; re-extraction
+    /// keeps it instead of overwriting it
+    pub manually_edited: bool,
+    /// Structured LLM output, also kept in `llm_extracted_context`; `None`
+    /// for failed entries
+    pub key_details: Option<Vec<String>>,
+    pub technologies: Option<Vec<String>>,
+    pub impact: Option<String>,
+}
+
+/// Result of comparing the repository fingerprint (root commit hash) stored
+/// in the `meta` table with the current repository's
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FingerprintStatus {
+    Matches { fingerprint: String },
+    /// Nothing was stored yet, so `fingerprint` has just been recorded
+    Recorded { fingerprint: String },
+    /// The database was created for a different repository
+    Mismatch { stored: String, current: String },
+    /// The repository has no commits to fingerprint yet
+    Unknown,
+}
+
+/// Column list matching `GlobalContext::from_row`
+const GLOBAL_CONTEXT_COLUMNS: &str = "id, commit_hash, commit_message, commit_date, context_summary,
+     files_changed, llm_extracted_context, created_at, model, prompt_version, author, extraction_failed,
+     branch, insertions, deletions, manually_edited, key_details, technologies, impact";
+
+impl GlobalContext {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(GlobalContext {
+            id: row.get(0)?,
+            commit_hash: row.get(1)?,
+            commit_message: row.get(2)?,
+            commit_date: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            context_summary: row.get(4)?,
+            files_changed: row.get(5)?,
+            llm_extracted_context: row.get(6)?,
+            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            model: row.get(8)?,
+            prompt_version: row.get(9)?,
+            author: row.get(10)?,
+            extraction_failed: row.get(11)?,
+            branch: row.get(12)?,
+            insertions: row.get(13)?,
+            deletions: row.get(14)?,
+            manually_edited: row.get(15)?,
+            key_details: json_column(row, 16)?,
+            technologies: json_column(row, 17)?,
+            impact: row.get(18)?,
+        })
+    }
+
+    /// Text embedded for semantic search: subject line, summary and key details
+    pub fn embedding_text(&self) -> String {
+        let mut text = format!(
+            "{}\n{}",
+            self.commit_message.lines().next().unwrap_or(""),
+            self.context_summary
+        );
+        for detail in self.key_details.iter().flatten() {
+            text.push('\n');
+            text.push_str(detail);
+        }
+        text
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -27,19 +114,235 @@ pub struct TtlMemory {
     pub created_at: DateTime<Utc>,
 }
 
+/// One `search` result. Matched terms in `snippet` are wrapped in
+/// `MATCH_START`/`MATCH_END` for the caller to highlight.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub context: GlobalContext,
+    pub snippet: String,
+}
+
+/// One `semantic_search` result, with the cosine similarity of its
+/// embedding to the query's (1.0 = same direction)
+#[derive(Debug, Clone)]
+pub struct SemanticHit {
+    pub context: GlobalContext,
+    pub similarity: f32,
+}
+
+pub const MATCH_START: &str = "\u{2}";
+pub const MATCH_END: &str = "\u{3}";
+
+/// What produced a stored entry, recorded alongside it
+pub struct EntryOrigin<'a> {
+    pub model: &'a str,
+    pub prompt_version: &'a str,
+    /// Branch checked out at processing time
+    pub branch: Option<&'a str>,
+}
+
+/// Rows removed by `delete_commits`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeletedRows {
+    /// Context entries (`global_context`)
+    pub entries: usize,
+    /// TTL memory rows
+    pub ttl_memory: usize,
+}
+
 pub struct Storage {
     conn: Connection,
 }
 
+/// Columns added after the initial schema: (table, column, declaration)
+const MIGRATED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("global_context", "model", "TEXT"),
+    ("global_context", "prompt_version", "TEXT"),
+    ("global_context", "author", "TEXT"),
+    ("global_context", "extraction_failed", "INTEGER NOT NULL DEFAULT 0"),
+    ("global_context", "branch", "TEXT"),
+    ("global_context", "insertions", "INTEGER"),
+    ("global_context", "deletions", "INTEGER"),
+    ("global_context", "manually_edited", "INTEGER NOT NULL DEFAULT 0"),
+    ("global_context", "key_details", "TEXT"),
+    ("global_context", "technologies", "TEXT"),
+    ("global_context", "impact", "TEXT"),
+];
+
+const TABLES: &[&str] = &["global_context", "ttl_memory", "diffs", "tags", "meta", "schema_version"];
+
+/// A versioned schema change; runs inside the transaction that records the
+/// new version
+type Migration = fn(&Connection) -> rusqlite::Result<()>;
+
+/// Schema changes after version 1, applied in order by `migrate`: entry `i`
+/// takes a database from version `i + 1` to `i + 2`. Append new entries;
+/// never edit or reorder released ones. (Columns in `MIGRATED_COLUMNS`
+/// predate versioning and are still added on every open.)
+const MIGRATIONS: &[Migration] = &[
+    // 2: fill the structured columns of entries stored before they existed
+    |conn| {
+        conn.execute(
+            "UPDATE global_context SET
+                key_details = json_extract(llm_extracted_context, '$.key_details'),
+                technologies = json_extract(llm_extracted_context, '$.technologies'),
+                impact = json_extract(llm_extracted_context, '$.impact')
+             WHERE extraction_failed = 0 AND key_details IS NULL AND json_valid(llm_extracted_context)",
+            [],
+        )?;
+        Ok(())
+    },
+    // 3: vectors for semantic search, one per entry, from `model`
+    |conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                commit_hash TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                vector BLOB NOT NULL
+            )",
+        )
+    },
+];
+
+/// Version a fully migrated database records in `schema_version`
+const SCHEMA_VERSION: usize = MIGRATIONS.len() + 1;
+
+/// FTS5 index mirroring the searchable text of `global_context`. Only
+/// created when SQLite was built with FTS5; `search` falls back to a LIKE
+/// scan without it.
+const SEARCH_TABLE: &str = "context_fts";
+
+/// Characters of context kept around a match in fallback snippets
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+const FINGERPRINT_KEY: &str = "repo_fingerprint";
+
+/// Tag marking which repository an entry in the global store came from,
+/// e.g. `repo:contexthub`
+pub const REPO_TAG_PREFIX: &str = "repo:";
+
 impl Storage {
     pub fn new(db_path: &PathBuf) -> anyhow::Result<Self> {
+        Self::open(db_path).map_err(|e| corruption_hint(db_path, e))
+    }
+
+    /// Read-only connection for query-only commands (`context`, `search`,
+    /// `status`, `memory`), so they never contend for the write lock with a concurrent
+    /// `sync` from the hook. An out-of-date schema is migrated first through a
+    /// normal read-write open.
+    pub fn open_readonly(db_path: &PathBuf) -> anyhow::Result<Self> {
+        let storage = Self::open_readonly_raw(db_path)?;
+        if storage.schema_is_current()? {
+            return Ok(storage);
+        }
+        drop(storage);
+        Self::new(db_path)?;
+        Self::open_readonly_raw(db_path)
+    }
+
+    /// Like `open_readonly`, but fails instead of migrating an out-of-date
+    /// schema, for commands that promise to leave the database untouched
+    /// (`sync --dry-run`)
+    pub fn open_without_migrating(db_path: &PathBuf) -> anyhow::Result<Self> {
+        let storage = Self::open_readonly_raw(db_path)?;
+        if !storage.schema_is_current()? {
+            anyhow::bail!("The context database needs migrating; run `contexthub sync` once to migrate");
+        }
+        Ok(storage)
+    }
+
+    fn open_readonly_raw(db_path: &PathBuf) -> anyhow::Result<Self> {
+        let open = || -> anyhow::Result<Self> {
+            let conn = Connection::open_with_flags(
+                db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?;
+            conn.execute_batch("PRAGMA busy_timeout=5000;")?;
+            Ok(Self { conn })
+        };
+        open().map_err(|e| corruption_hint(db_path, e))
+    }
+
+    fn schema_is_current(&self) -> anyhow::Result<bool> {
+        for table in TABLES {
+            if self.columns(table)?.is_empty() {
+                return Ok(false);
+            }
+        }
+        for (table, column, _) in MIGRATED_COLUMNS {
+            if !self.columns(table)?.iter().any(|c| c == column) {
+                return Ok(false);
+            }
+        }
+        if self.fts_supported()? && !self.has_search_index()? {
+            return Ok(false);
+        }
+        Ok(self.schema_version()? == Some(SCHEMA_VERSION))
+    }
+
+    fn columns(&self, table: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(columns)
+    }
+
+    fn open(db_path: &PathBuf) -> anyhow::Result<Self> {
         let conn = Connection::open(db_path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")?;
         let storage = Self { conn };
         storage.init_tables()?;
+        storage.migrate()?;
         Ok(storage)
     }
 
+    /// Read-only connection to the cross-repository store, `None` if no sync
+    /// has mirrored anything into it yet
+    pub fn open_global_readonly() -> anyhow::Result<Option<Self>> {
+        let db_path = global_db_path()?;
+        if !db_path.exists() {
+            return Ok(None);
+        }
+        Self::open_readonly(&db_path).map(Some)
+    }
+
+    /// The cross-repository store, created on first use
+    pub fn open_global() -> anyhow::Result<Self> {
+        let db_path = global_db_path()?;
+        if let Some(dir) = db_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Self::new(&db_path)
+    }
+
+    /// Move a corrupt database (and its WAL/SHM files) aside and create a
+    /// fresh one in its place. Returns the backup path.
+    pub fn backup_and_reset(db_path: &PathBuf) -> anyhow::Result<PathBuf> {
+        let backup = db_path.with_extension(format!(
+            "db.corrupt-{}",
+            Utc::now().format("%Y%m%d%H%M%S")
+        ));
+        std::fs::rename(db_path, &backup)?;
+        for suffix in ["-wal", "-shm"] {
+            let sidecar = PathBuf::from(format!("{}{}", db_path.display(), suffix));
+            if sidecar.exists() {
+                std::fs::remove_file(&sidecar)?;
+            }
+        }
+        Self::open(db_path)?;
+        Ok(backup)
+    }
+
+    /// Runs `PRAGMA integrity_check`; `true` when SQLite reports "ok"
+    pub fn integrity_check(&self) -> anyhow::Result<bool> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let results = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(results.len() == 1 && results[0] == "ok")
+    }
+
     fn init_tables(&self) -> anyhow::Result<()> {
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS global_context (
@@ -66,6 +369,47 @@ impl Storage {
             [],
         )?;
 
+        // Preprocessed diffs, only written when `context.store_diffs` is on
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS diffs (
+                commit_hash TEXT PRIMARY KEY,
+                diff TEXT NOT NULL,
+                stored_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // User tags parsed from commit messages (`#tag`, `Context:` trailers)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                commit_hash TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (commit_hash, tag)
+            )",
+            [],
+        )?;
+
+        // Single row holding the version `migrate` brought the schema to
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Database-level facts, such as the repository fingerprint
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        for (table, column, decl) in MIGRATED_COLUMNS {
+            self.add_column_if_missing(table, column, decl)?;
+        }
+
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_global_commit ON global_context(commit_hash)",
             [],
@@ -81,9 +425,180 @@ impl Storage {
             [],
         )?;
 
+        if self.fts_supported()? {
+            self.conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING fts5(
+                        commit_hash UNINDEXED, commit_message, context_summary, llm_extracted_context
+                    )",
+                    SEARCH_TABLE
+                ),
+                [],
+            )?;
+            self.backfill_search_index()?;
+        }
+
         Ok(())
     }
 
+    fn fts_supported(&self) -> anyhow::Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT sqlite_compileoption_used('ENABLE_FTS5')", [], |row| row.get(0))?)
+    }
+
+    fn has_search_index(&self) -> anyhow::Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [SEARCH_TABLE],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Rebuild the search index when it is out of step with `global_context`,
+    /// e.g. for a database created before the index existed
+    fn backfill_search_index(&self) -> anyhow::Result<()> {
+        let (indexed, stored): (i64, i64) = self.conn.query_row(
+            &format!(
+                "SELECT (SELECT COUNT(*) FROM {}), (SELECT COUNT(*) FROM global_context)",
+                SEARCH_TABLE
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if indexed == stored {
+            return Ok(());
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(&format!("DELETE FROM {}", SEARCH_TABLE), [])?;
+        tx.execute(
+            &format!(
+                "INSERT INTO {} (commit_hash, commit_message, context_summary, llm_extracted_context)
+                 SELECT commit_hash, commit_message, context_summary, llm_extracted_context FROM global_context",
+                SEARCH_TABLE
+            ),
+            [],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Refresh what is derived from an entry's text after it was written: the
+    /// search index row is rebuilt and the embedding dropped, to be
+    /// recomputed by the next `search --semantic`
+    fn entry_changed(&self, commit_hash: &str) -> anyhow::Result<()> {
+        self.reindex_search_entry(commit_hash)?;
+        self.conn.execute("DELETE FROM embeddings WHERE commit_hash = ?1", [commit_hash])?;
+        Ok(())
+    }
+
+    /// Bring the search index entry for `commit_hash` in line with the table
+    fn reindex_search_entry(&self, commit_hash: &str) -> anyhow::Result<()> {
+        if !self.has_search_index()? {
+            return Ok(());
+        }
+        self.conn.execute(
+            &format!("DELETE FROM {} WHERE commit_hash = ?1", SEARCH_TABLE),
+            [commit_hash],
+        )?;
+        self.conn.execute(
+            &format!(
+                "INSERT INTO {} (commit_hash, commit_message, context_summary, llm_extracted_context)
+                 SELECT commit_hash, commit_message, context_summary, llm_extracted_context
+                 FROM global_context WHERE commit_hash = ?1",
+                SEARCH_TABLE
+            ),
+            [commit_hash],
+        )?;
+        Ok(())
+    }
+
+    fn add_column_if_missing(&self, table: &str, column: &str, decl: &str) -> anyhow::Result<()> {
+        if !self.columns(table)?.iter().any(|name| name == column) {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl),
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Version recorded in `schema_version`, `None` before the first `migrate`
+    fn schema_version(&self) -> anyhow::Result<Option<usize>> {
+        let version: Option<i64> = self
+            .conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))?;
+        Ok(version.map(|v| v as usize))
+    }
+
+    /// Apply the pending `MIGRATIONS`, each in a transaction together with
+    /// the version bump. A database without a recorded version has the base
+    /// tables from `init_tables` and starts at version 1.
+    fn migrate(&self) -> anyhow::Result<()> {
+        let version = match self.schema_version()? {
+            Some(version) => version,
+            None => {
+                self.conn.execute("INSERT INTO schema_version (version) VALUES (1)", [])?;
+                1
+            }
+        };
+        if version > SCHEMA_VERSION {
+            anyhow::bail!(
+                "The database uses schema version {}, but this contexthub only supports up to {}. Upgrade contexthub.",
+                version,
+                SCHEMA_VERSION
+            );
+        }
+
+        for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version - 1) {
+            let next = idx + 2;
+            let tx = self.conn.unchecked_transaction()?;
+            migration(&tx).map_err(|e| anyhow::anyhow!("Schema migration to version {} failed: {}", next, e))?;
+            tx.execute("UPDATE schema_version SET version = ?1", [next as i64])?;
+            tx.commit()?;
+            log::info!("Migrated database schema to version {}", next);
+        }
+        Ok(())
+    }
+
+    fn get_meta(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?;
+        Ok(value)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Compare the stored repository fingerprint with `current` (the root
+    /// commit of the repository in use), recording it if none is stored yet.
+    pub fn check_fingerprint(&self, current: Option<&str>) -> anyhow::Result<FingerprintStatus> {
+        let Some(current) = current else {
+            return Ok(FingerprintStatus::Unknown);
+        };
+        match self.get_meta(FINGERPRINT_KEY)? {
+            Some(stored) if stored == current => Ok(FingerprintStatus::Matches { fingerprint: stored }),
+            Some(stored) => Ok(FingerprintStatus::Mismatch {
+                stored,
+                current: current.to_string(),
+            }),
+            None => {
+                self.set_meta(FINGERPRINT_KEY, current)?;
+                Ok(FingerprintStatus::Recorded {
+                    fingerprint: current.to_string(),
+                })
+            }
+        }
+    }
+
     /// Check if a commit has already been processed (for dedup)
     pub fn has_commit(&self, commit_hash: &str) -> anyhow::Result<bool> {
         let count: i64 = self.conn.query_row(
@@ -94,102 +609,569 @@ impl Storage {
         Ok(count > 0)
     }
 
+    /// Every stored commit hash, including failed extractions
+    pub fn all_hashes(&self) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT commit_hash FROM global_context")?;
+        let hashes = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hashes)
+    }
+
+    /// Remove everything stored for the given commits. Returns how many
+    /// context entries and TTL memory rows were deleted.
+    pub fn delete_commits(&self, hashes: &[String]) -> anyhow::Result<DeletedRows> {
+        let mut tables = vec!["diffs", "tags", "embeddings"];
+        if self.has_search_index()? {
+            tables.push(SEARCH_TABLE);
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let mut deleted = DeletedRows::default();
+        for hash in hashes {
+            deleted.entries += tx.execute("DELETE FROM global_context WHERE commit_hash = ?1", [hash])?;
+            deleted.ttl_memory += tx.execute("DELETE FROM ttl_memory WHERE commit_hash = ?1", [hash])?;
+            for table in &tables {
+                tx.execute(&format!("DELETE FROM {} WHERE commit_hash = ?1", table), [hash])?;
+            }
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Delete the entry for a full hash or unique prefix together with its
+    /// TTL memory, stored diff, tags, embedding and search index row. Nothing
+    /// is deleted if no entry matches.
+    pub fn delete_context(&self, hash_prefix: &str) -> anyhow::Result<DeletedRows> {
+        let Some(ctx) = self.get_context_by_hash(hash_prefix)? else {
+            return Ok(DeletedRows::default());
+        };
+        self.delete_commits(&[ctx.commit_hash])
+    }
+
     pub fn store_global_context(
         &self,
         commit: &CommitInfo,
-        context_summary: &str,
+        context: &ExtractedContext,
         files_changed: &[String],
-        llm_extracted_json: &str,
+        origin: &EntryOrigin<'_>,
     ) -> anyhow::Result<()> {
         let files_json = serde_json::to_string(files_changed)?;
+        let extracted_json = serde_json::to_string(context)?;
+        // A hand-corrected summary survives re-syncs and reindexing
+        let edited = self.edited_summary(&commit.hash)?;
 
         self.conn.execute(
             "INSERT OR REPLACE INTO global_context 
-             (commit_hash, commit_message, commit_date, context_summary, files_changed, llm_extracted_context)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+             (commit_hash, commit_message, commit_date, context_summary, files_changed, llm_extracted_context,
+              model, prompt_version, author, extraction_failed, branch, insertions, deletions, manually_edited,
+              key_details, technologies, impact)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 commit.hash,
                 commit.message,
                 commit.date.to_rfc3339(),
-                context_summary,
+                edited.as_deref().unwrap_or(&context.summary),
                 files_json,
-                llm_extracted_json,
+                extracted_json,
+                origin.model,
+                origin.prompt_version,
+                commit.author,
+                origin.branch,
+                commit.insertions,
+                commit.deletions,
+                edited.is_some(),
+                serde_json::to_string(&context.key_details)?,
+                serde_json::to_string(&context.technologies)?,
+                context.impact,
             ],
         )?;
+        self.entry_changed(&commit.hash)?;
 
         Ok(())
     }
 
-    /// Get the most recently stored context summary for incremental chaining
-    pub fn get_latest_context_summary(&self) -> anyhow::Result<Option<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT context_summary FROM global_context ORDER BY commit_date DESC LIMIT 1",
+    /// Record a commit whose extraction failed, so it isn't silently retried
+    /// on every sync; the placeholder summary is never exported. A manually
+    /// edited entry is left as it is.
+    pub fn store_failed_context(
+        &self,
+        commit: &CommitInfo,
+        reason: &str,
+        files_changed: &[String],
+        origin: &EntryOrigin<'_>,
+    ) -> anyhow::Result<()> {
+        if self.edited_summary(&commit.hash)?.is_some() {
+            return Ok(());
+        }
+        let files_json = serde_json::to_string(files_changed)?;
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO global_context 
+             (commit_hash, commit_message, commit_date, context_summary, files_changed, llm_extracted_context,
+              model, prompt_version, author, extraction_failed, branch, insertions, deletions)
+             VALUES (?1, ?2, ?3, ?4, ?5, '', ?6, ?7, ?8, 1, ?9, ?10, ?11)",
+            params![
+                commit.hash,
+                commit.message,
+                commit.date.to_rfc3339(),
+                format!("Extraction failed: {}", reason),
+                files_json,
+                origin.model,
+                origin.prompt_version,
+                commit.author,
+                origin.branch,
+                commit.insertions,
+                commit.deletions,
+            ],
         )?;
-        let result = stmt.query_row([], |row| row.get(0)).ok();
-        Ok(result)
+        self.entry_changed(&commit.hash)?;
+
+        Ok(())
     }
 
-    pub fn get_global_context(&self) -> anyhow::Result<Vec<GlobalContext>> {
+    /// Replace an entry's summary with hand-written text and mark it
+    /// `manually_edited`. A failed entry becomes a normal one, since it now
+    /// has a summary worth exporting. Returns false if there is no entry.
+    pub fn update_summary(&self, commit_hash: &str, summary: &str) -> anyhow::Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE global_context SET context_summary = ?2, manually_edited = 1, extraction_failed = 0
+             WHERE commit_hash = ?1",
+            params![commit_hash, summary],
+        )?;
+        if updated > 0 {
+            self.entry_changed(commit_hash)?;
+        }
+        Ok(updated > 0)
+    }
+
+    /// Entries whose message, summary or extracted context match `query`,
+    /// best match first. Every whitespace-separated term must appear. Uses
+    /// the FTS5 index when there is one, otherwise a slower LIKE scan that
+    /// matches `query` as a single substring, newest first.
+    pub fn search(&self, query: &str, limit: usize) -> anyhow::Result<Vec<SearchHit>> {
+        let limit = limit.min(i64::MAX as usize) as i64;
+        if !self.has_search_index()? {
+            log::warn!("SQLite was built without FTS5; searching with a LIKE scan");
+            return self.search_like(query, limit);
+        }
+
+        // Quote every term so punctuation in the query is matched literally
+        // instead of being read as FTS5 syntax
+        let fts_query = query
+            .split_whitespace()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // One snippet per column (summary, message, extracted context); the
+        // first one containing a match is shown, so the readable summary wins
+        // over the raw JSON
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, hit_summary, hit_message, hit_extracted FROM global_context
+             JOIN (SELECT commit_hash AS hit_hash, rank AS hit_rank,
+                          snippet({table}, 2, ?3, ?4, '…', 16) AS hit_summary,
+                          snippet({table}, 1, ?3, ?4, '…', 16) AS hit_message,
+                          snippet({table}, 3, ?3, ?4, '…', 16) AS hit_extracted
+                   FROM {table} WHERE {table} MATCH ?1)
+               ON commit_hash = hit_hash
+             WHERE extraction_failed = 0
+             ORDER BY hit_rank LIMIT ?2",
+            GLOBAL_CONTEXT_COLUMNS,
+            table = SEARCH_TABLE
+        ))?;
+        let hits = stmt
+            .query_map(params![fts_query, limit, MATCH_START, MATCH_END], |row| {
+                let snippets: [Option<String>; 3] = [row.get(19)?, row.get(20)?, row.get(21)?];
+                Ok(SearchHit {
+                    context: GlobalContext::from_row(row)?,
+                    snippet: snippets
+                        .into_iter()
+                        .flatten()
+                        .find(|s| s.contains(MATCH_START))
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hits)
+    }
+
+    fn search_like(&self, query: &str, limit: i64) -> anyhow::Result<Vec<SearchHit>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context
+             WHERE extraction_failed = 0
+               AND (commit_message LIKE ?1 ESCAPE '\\' OR context_summary LIKE ?1 ESCAPE '\\'
+                    OR llm_extracted_context LIKE ?1 ESCAPE '\\')
+             ORDER BY commit_date DESC, commit_hash DESC LIMIT ?2",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+        let contexts = stmt
+            .query_map(params![pattern, limit], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contexts
+            .into_iter()
+            .map(|context| {
+                let snippet = [
+                    &context.context_summary,
+                    &context.commit_message,
+                    &context.llm_extracted_context,
+                ]
+                .into_iter()
+                .find_map(|text| highlight_substring(text, query))
+                .unwrap_or_default();
+                SearchHit { context, snippet }
+            })
+            .collect())
+    }
+
+    pub fn store_embedding(&self, commit_hash: &str, model: &str, vector: &[f32]) -> anyhow::Result<()> {
+        let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO embeddings (commit_hash, model, vector) VALUES (?1, ?2, ?3)",
+            params![commit_hash, model, blob],
+        )?;
+        Ok(())
+    }
+
+    /// Entries without an embedding from `model`, newest first
+    pub fn get_unembedded(&self, model: &str) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context
+             WHERE extraction_failed = 0
+               AND commit_hash NOT IN (SELECT commit_hash FROM embeddings WHERE model = ?1)
+             ORDER BY commit_date DESC, commit_hash DESC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+        let contexts = stmt
+            .query_map([model], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(contexts)
+    }
+
+    /// The `top_k` entries whose embeddings are most similar to `query_vec`
+    /// by cosine similarity, best first. Vectors of a different length (from
+    /// another model) are skipped.
+    pub fn semantic_search(&self, query_vec: &[f32], top_k: usize) -> anyhow::Result<Vec<SemanticHit>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, commit_hash, commit_message, commit_date, context_summary, 
-                    files_changed, llm_extracted_context, created_at
-             FROM global_context ORDER BY commit_date DESC",
+            "SELECT e.commit_hash, e.vector FROM embeddings e
+             JOIN global_context g ON g.commit_hash = e.commit_hash
+             WHERE g.extraction_failed = 0",
         )?;
+        let mut scored: Vec<(String, f32)> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)))?
+            .filter_map(|row| match row {
+                Ok((hash, blob)) => {
+                    let vector: Vec<f32> = blob
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect();
+                    cosine_similarity(query_vec, &vector).map(|score| Ok((hash, score)))
+                }
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(top_k);
+
+        let mut hits = Vec::with_capacity(scored.len());
+        for (hash, similarity) in scored {
+            if let Some(context) = self.get_context_by_hash(&hash)? {
+                hits.push(SemanticHit { context, similarity });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// The summary of `commit_hash` if it was edited by hand
+    fn edited_summary(&self, commit_hash: &str) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT context_summary FROM global_context WHERE commit_hash = ?1 AND manually_edited = 1",
+                [commit_hash],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Entries flagged `extraction_failed`, oldest first
+    pub fn get_failed_contexts(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context WHERE extraction_failed = 1 ORDER BY commit_date ASC, commit_hash ASC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
 
         let contexts = stmt
-            .query_map([], |row| {
-                Ok(GlobalContext {
-                    id: row.get(0)?,
-                    commit_hash: row.get(1)?,
-                    commit_message: row.get(2)?,
-                    commit_date: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    context_summary: row.get(4)?,
-                    files_changed: row.get(5)?,
-                    llm_extracted_context: row.get(6)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                })
+            .query_map([], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contexts)
+    }
+
+    pub fn get_failed_count(&self) -> anyhow::Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM global_context WHERE extraction_failed = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    pub fn store_diff(&self, commit_hash: &str, diff: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO diffs (commit_hash, diff) VALUES (?1, ?2)",
+            params![commit_hash, diff],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_stored_diff(&self, commit_hash: &str) -> anyhow::Result<Option<String>> {
+        let diff = self
+            .conn
+            .query_row(
+                "SELECT diff FROM diffs WHERE commit_hash = ?1",
+                [commit_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(diff)
+    }
+
+    /// Replace the tags recorded for a commit
+    pub fn store_tags(&self, commit_hash: &str, tags: &[String]) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM tags WHERE commit_hash = ?1", [commit_hash])?;
+        for tag in tags {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO tags (commit_hash, tag) VALUES (?1, ?2)",
+                params![commit_hash, tag],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Repository name of every entry tagged with `REPO_TAG_PREFIX`, by hash
+    pub fn get_repo_names(&self) -> anyhow::Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT commit_hash, tag FROM tags WHERE substr(tag, 1, ?2) = ?1")?;
+        let names = stmt
+            .query_map(params![REPO_TAG_PREFIX, REPO_TAG_PREFIX.len()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
             })?
+            .map(|row| row.map(|(hash, tag)| (hash, tag[REPO_TAG_PREFIX.len()..].to_string())))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(names)
+    }
+
+    /// Hashes of commits carrying `tag`
+    pub fn get_tagged_hashes(&self, tag: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT commit_hash FROM tags WHERE tag = ?1")?;
+        let hashes = stmt
+            .query_map([tag], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hashes)
+    }
+
+    /// Number of stored entries per tag, most used first
+    pub fn get_tag_counts(&self) -> anyhow::Result<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.tag, COUNT(*) FROM tags t
+             JOIN global_context g ON g.commit_hash = t.commit_hash
+             WHERE g.extraction_failed = 0
+             GROUP BY t.tag ORDER BY COUNT(*) DESC, t.tag ASC",
+        )?;
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    /// Entries that have a stored diff, oldest first
+    pub fn get_global_context_with_diffs(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context
+             WHERE commit_hash IN (SELECT commit_hash FROM diffs)
+             ORDER BY commit_date ASC, commit_hash ASC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        let contexts = stmt
+            .query_map([], GlobalContext::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(contexts)
     }
 
+    /// Get the `n` most recent context summaries for incremental chaining, oldest first
+    pub fn get_recent_summaries(&self, n: usize) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT context_summary FROM global_context WHERE extraction_failed = 0
+             ORDER BY commit_date DESC, commit_hash DESC LIMIT ?1",
+        )?;
+        let mut summaries = stmt
+            .query_map([n as i64], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        summaries.reverse();
+        Ok(summaries)
+    }
+
+    /// Newest first; same-second commits are ordered by hash so exports
+    /// come out identical on every run
+    pub fn get_global_context(&self) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context WHERE extraction_failed = 0 ORDER BY commit_date DESC, commit_hash DESC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        let contexts = stmt
+            .query_map([], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contexts)
+    }
+
+    /// Listable entries whose `files_changed` contains `file`, or a file
+    /// under it when `file` is a directory, oldest first
+    pub fn get_global_context_for_file(&self, file: &str) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context
+             WHERE extraction_failed = 0
+               AND EXISTS (
+                   SELECT 1 FROM json_each(CASE WHEN json_valid(files_changed) THEN files_changed ELSE '[]' END)
+                   WHERE value = ?1 OR value LIKE ?1 || '/%'
+               )
+             ORDER BY commit_date ASC, commit_hash ASC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        let contexts = stmt
+            .query_map([file], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contexts)
+    }
+
+    /// Call `f` for each listable entry, newest first, reading rows one at a
+    /// time instead of collecting them. `f` returns `false` to stop early.
+    pub fn for_each_global_context(
+        &self,
+        mut f: impl FnMut(GlobalContext) -> anyhow::Result<bool>,
+    ) -> anyhow::Result<()> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context WHERE extraction_failed = 0 ORDER BY commit_date DESC, commit_hash DESC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        for ctx in stmt.query_map([], GlobalContext::from_row)? {
+            if !f(ctx?)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Entries whose author contains `author` (case-insensitive), newest first
+    pub fn get_global_context_by_author(&self, author: &str) -> anyhow::Result<Vec<GlobalContext>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context
+             WHERE author LIKE '%' || ?1 || '%' AND extraction_failed = 0
+             ORDER BY commit_date DESC, commit_hash DESC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        let contexts = stmt
+            .query_map([author], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contexts)
+    }
+
+    /// The stored `ExtractedContext` (key details, technologies, impact) for
+    /// a full commit hash, without re-running the LLM. `None` when there is
+    /// no entry or its `llm_extracted_context` doesn't parse (failed entries).
+    pub fn get_extracted_context(&self, commit_hash: &str) -> anyhow::Result<Option<ExtractedContext>> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT llm_extracted_context FROM global_context WHERE commit_hash = ?1",
+                [commit_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Look up a single entry by full hash or unique hash prefix
+    pub fn get_context_by_hash(&self, hash_prefix: &str) -> anyhow::Result<Option<GlobalContext>> {
+        if hash_prefix.is_empty() || !hash_prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("'{}' is not a commit hash or hash prefix", hash_prefix);
+        }
+        let hash_prefix = hash_prefix.to_ascii_lowercase();
+
+        // Compare literally rather than with LIKE so the prefix is never a pattern
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context WHERE substr(commit_hash, 1, length(?1)) = ?1 LIMIT 2",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
+
+        let mut matches = stmt
+            .query_map([&hash_prefix], GlobalContext::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if matches.len() > 1 {
+            anyhow::bail!(
+                "Commit prefix '{}' is ambiguous: it matches more than one stored entry. Use more characters.",
+                hash_prefix
+            );
+        }
+        Ok(matches.pop())
+    }
+
+    /// Hashes of entries generated with a different model or prompt version
+    /// (including entries that predate tracking), newest first
+    pub fn get_stale_commits(&self, model: &str, prompt_version: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT commit_hash FROM global_context
+             WHERE model IS NOT ?1 OR prompt_version IS NOT ?2
+             ORDER BY commit_date DESC, commit_hash DESC",
+        )?;
+
+        let hashes = stmt
+            .query_map([model, prompt_version], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(hashes)
+    }
+
     #[allow(dead_code)]
     pub fn get_global_context_since(
         &self,
         commit_hash: &str,
     ) -> anyhow::Result<Vec<GlobalContext>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, commit_hash, commit_message, commit_date, context_summary, 
-                    files_changed, llm_extracted_context, created_at
-             FROM global_context 
-             WHERE commit_hash = ?1 OR commit_date >= (
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM global_context 
+             WHERE (commit_hash = ?1 OR commit_date >= (
                  SELECT commit_date FROM global_context WHERE commit_hash = ?1
-             )
-             ORDER BY commit_date DESC",
-        )?;
+             )) AND extraction_failed = 0
+             ORDER BY commit_date DESC, commit_hash DESC",
+            GLOBAL_CONTEXT_COLUMNS
+        ))?;
 
         let contexts = stmt
-            .query_map([commit_hash], |row| {
-                Ok(GlobalContext {
-                    id: row.get(0)?,
-                    commit_hash: row.get(1)?,
-                    commit_message: row.get(2)?,
-                    commit_date: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    context_summary: row.get(4)?,
-                    files_changed: row.get(5)?,
-                    llm_extracted_context: row.get(6)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                })
-            })?
+            .query_map([commit_hash], GlobalContext::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(contexts)
@@ -198,7 +1180,7 @@ impl Storage {
     pub fn get_last_processed_commit(&self) -> anyhow::Result<Option<String>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT commit_hash FROM global_context ORDER BY commit_date DESC LIMIT 1")?;
+            .prepare("SELECT commit_hash FROM global_context ORDER BY commit_date DESC, commit_hash DESC LIMIT 1")?;
 
         let result = stmt.query_row([], |row| row.get(0)).ok();
         Ok(result)
@@ -254,6 +1236,17 @@ impl Storage {
         Ok(())
     }
 
+    /// How many TTL entries `cleanup_expired_ttl` would delete now
+    pub fn count_expired_ttl(&self) -> anyhow::Result<usize> {
+        let now = Utc::now().to_rfc3339();
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM ttl_memory WHERE expires_at <= ?1",
+            [now],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
     pub fn cleanup_expired_ttl(&self) -> anyhow::Result<usize> {
         let now = Utc::now().to_rfc3339();
         let deleted = self
@@ -262,6 +1255,34 @@ impl Storage {
         Ok(deleted)
     }
 
+    /// Rebuild the database file to reclaim pages freed by deletions.
+    /// The WAL is checkpointed before and truncated after so the main file
+    /// holds every committed page and the reported size is accurate.
+    pub fn vacuum(&self) -> anyhow::Result<()> {
+        self.conn.execute_batch(
+            "PRAGMA wal_checkpoint(TRUNCATE); VACUUM; ANALYZE; PRAGMA wal_checkpoint(TRUNCATE);",
+        )?;
+        Ok(())
+    }
+
+    /// Total lines added and removed across listable entries, and how many
+    /// entries have line counts recorded
+    pub fn get_churn_totals(&self) -> anyhow::Result<(usize, usize, usize)> {
+        let totals = self.conn.query_row(
+            "SELECT COALESCE(SUM(insertions), 0), COALESCE(SUM(deletions), 0), COUNT(insertions)
+             FROM global_context WHERE extraction_failed = 0",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                    row.get::<_, i64>(2)? as usize,
+                ))
+            },
+        )?;
+        Ok(totals)
+    }
+
     pub fn get_context_count(&self) -> anyhow::Result<usize> {
         let count: i64 = self
             .conn
@@ -269,3 +1290,141 @@ impl Storage {
         Ok(count as usize)
     }
 }
+
+fn corruption_hint(db_path: &Path, e: anyhow::Error) -> anyhow::Error {
+    if is_corruption_error(&e) {
+        let msg = format!(
+            "Database {} appears corrupt.\nRun 'contexthub doctor --fix' to back it up and start fresh.",
+            db_path.display()
+        );
+        e.context(msg)
+    } else {
+        e
+    }
+}
+
+/// A JSON-encoded TEXT column; NULL or unparseable text reads as `None`
+fn json_column<T: serde::de::DeserializeOwned>(
+    row: &rusqlite::Row<'_>,
+    idx: usize,
+) -> rusqlite::Result<Option<T>> {
+    Ok(row
+        .get::<_, Option<String>>(idx)?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Whether an error from opening the database means the file itself is damaged
+/// (as opposed to e.g. a permissions problem or a locked database).
+pub fn is_corruption_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<rusqlite::Error>(),
+        Some(rusqlite::Error::SqliteFailure(e, _))
+            if matches!(e.code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
+
+/// The first case-insensitive (ASCII, like SQLite's LIKE) occurrence of
+/// `needle` in `text`, marked up and trimmed to a window around the match
+fn highlight_substring(text: &str, needle: &str) -> Option<String> {
+    let start = text.to_ascii_lowercase().find(&needle.to_ascii_lowercase())?;
+    let end = start + needle.len();
+
+    let mut from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    while !text.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + SNIPPET_CONTEXT_CHARS).min(text.len());
+    while !text.is_char_boundary(to) {
+        to += 1;
+    }
+
+    Some(format!(
+        "{}{}{}{}{}{}{}",
+        if from > 0 { "…" } else { "" },
+        &text[from..start],
+        MATCH_START,
+        &text[start..end],
+        MATCH_END,
+        &text[end..to],
+        if to < text.len() { "…" } else { "" },
+    ))
+}
+
+/// Cosine similarity of two vectors; `None` if their lengths differ or
+/// either is all zeros
+fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a * norm_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            message: "Add parser".to_string(),
+            author: "Test".to_string(),
+            date: Utc::now(),
+            parent_hashes: vec![],
+            insertions: None,
+            deletions: None,
+        }
+    }
+
+    fn origin() -> EntryOrigin<'static> {
+        EntryOrigin { model: "llama3.2", prompt_version: "1", branch: None }
+    }
+
+    #[test]
+    fn get_extracted_context_reads_stored_llm_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(&dir.path().join("context.db")).unwrap();
+        let extracted = ExtractedContext {
+            summary: "Add parser".to_string(),
+            files_changed: vec!["src/parser.rs".to_string()],
+            key_details: vec!["Recursive descent".to_string()],
+            technologies: vec!["rust".to_string()],
+            impact: "high".to_string(),
+        };
+        storage.store_global_context(&commit("aaaa"), &extracted, &extracted.files_changed, &origin()).unwrap();
+        storage.store_failed_context(&commit("bbbb"), "empty response", &[], &origin()).unwrap();
+
+        let stored = storage.get_extracted_context("aaaa").unwrap().expect("stored context");
+        assert_eq!(stored.key_details, extracted.key_details);
+        assert_eq!(stored.technologies, extracted.technologies);
+        assert_eq!(stored.impact, "high");
+        assert!(storage.get_extracted_context("bbbb").unwrap().is_none(), "unparsable row");
+        assert!(storage.get_extracted_context("cccc").unwrap().is_none(), "missing row");
+    }
+
+    #[test]
+    fn get_context_by_hash_rejects_patterns_and_empty_prefixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(&dir.path().join("context.db")).unwrap();
+        let extracted = ExtractedContext {
+            summary: "Add parser".to_string(),
+            files_changed: vec![],
+            key_details: vec![],
+            technologies: vec![],
+            impact: "low".to_string(),
+        };
+        storage.store_global_context(&commit("abc123"), &extracted, &[], &origin()).unwrap();
+
+        for prefix in ["", "%", "_", "ab%", "a_c"] {
+            assert!(storage.get_context_by_hash(prefix).is_err(), "prefix {:?} should be rejected", prefix);
+        }
+        assert_eq!(storage.get_context_by_hash("ABC").unwrap().unwrap().commit_hash, "abc123");
+        assert!(storage.get_context_by_hash("abd").unwrap().is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! Temporary git repositories for unit tests
+
+use git2::{Repository, Signature};
+use std::path::{Path, PathBuf};
+
+use crate::utils::config::Config;
+
+/// An empty repository in a temporary directory with `.contexthub/` created,
+/// removed again on drop
+pub struct TestRepo {
+    dir: tempfile::TempDir,
+    repo: Repository,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::create_dir_all(dir.path().join(".contexthub")).unwrap();
+        Self { dir, repo }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().to_path_buf()
+    }
+
+    pub fn write(&self, file: &str, contents: &str) {
+        let path = self.dir.path().join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[cfg(unix)]
+    pub fn set_executable(&self, file: &str, executable: bool) {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = if executable { 0o755 } else { 0o644 };
+        std::fs::set_permissions(self.dir.path().join(file), std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    /// Commit the whole work tree (outside `.contexthub/`) on top of HEAD and
+    /// return the new commit's hash
+    pub fn commit(&self, message: &str) -> String {
+        let mut index = self.repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, Some(&mut |path: &Path, _: &[u8]| {
+                i32::from(path.starts_with(".contexthub"))
+            }))
+            .unwrap();
+        index.write().unwrap();
+        let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+            .to_string()
+    }
+}
+
+/// Config whose LLM endpoint doesn't answer, without retries, embeddings or
+/// the response cache: any LLM call fails fast
+pub fn offline_config() -> Config {
+    let mut config = Config::default();
+    config.ollama.endpoint = "http://127.0.0.1:9".to_string();
+    config.ollama.max_retries = 0;
+    config.ollama.timeout_secs = 5;
+    config.ollama.cache = false;
+    config.ollama.embedding_model = String::new();
+    config
+}
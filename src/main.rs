@@ -1,7 +1,9 @@
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
 
+use utils::error::ContextHubError;
+
 mod commands;
 mod core;
 mod utils;
@@ -13,6 +15,15 @@ mod ui;
 #[command(version = "0.1.0")]
 #[command(about = "Global Context Storage for AI Coding Assistants", long_about = None)]
 struct Cli {
+    /// Override the Ollama endpoint for this invocation only
+    #[arg(long, global = true)]
+    endpoint: Option<String>,
+    /// Read (and save) settings from this file instead of `.contexthub/config.json`
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Increase log verbosity (-v: debug incl. LLM prompts, -vv: trace)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
     #[command(subcommand)]
     command: Commands,
 }
@@ -22,20 +33,146 @@ enum Commands {
     Init {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Never prompt: use defaults, install the hook and run the initial sync
+        #[arg(long, visible_alias = "yes")]
+        non_interactive: bool,
+        /// Ollama model to use instead of choosing one
+        #[arg(long)]
+        model: Option<String>,
+        /// Don't install the post-commit hook
+        #[arg(long)]
+        no_hook: bool,
+        /// Don't run the initial sync
+        #[arg(long)]
+        no_sync: bool,
     },
     Sync {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Process commits after this revision (hash, branch, tag, HEAD~5) up to HEAD, or an A..B range
         #[arg(short, long)]
         from: Option<String>,
+        /// Only process commits newer than this (e.g. 2024-01-15 or "2 weeks ago")
+        #[arg(long)]
+        since: Option<String>,
         #[arg(short, long)]
         last: Option<usize>,
+        /// Skip commits whose message matches this regex (repeatable)
+        #[arg(long = "skip-pattern")]
+        skip_patterns: Vec<String>,
+        /// Summarize submodule commits behind gitlink bumps
+        #[arg(long)]
+        include_submodules: bool,
+        /// Follow only the first parent of each commit (mainline history)
+        #[arg(long)]
+        first_parent: bool,
+        /// Regenerate entries produced by a different model or prompt version
+        #[arg(long, conflicts_with_all = ["from", "since", "last"])]
+        refresh_stale: bool,
+        /// Re-process entries whose extraction failed (e.g. empty LLM response)
+        #[arg(long, conflicts_with_all = ["from", "since", "last", "refresh_stale"])]
+        retry_failed: bool,
+        /// Check stored entries against the repository first and offer to prune missing commits
+        #[arg(long)]
+        verify: bool,
+        /// Stream each LLM response and show progress while it arrives (Ollama provider)
+        #[arg(long)]
+        stream: bool,
+        /// Call the LLM for every commit instead of reusing cached results
+        #[arg(long)]
+        no_cache: bool,
+        /// Commits to analyze concurrently; more than 1 turns off incremental chaining for the run
+        #[arg(short, long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "retry_failed")]
+        jobs: u16,
+        /// Copy high-impact entries into the global store (~/.config/contexthub/global.db)
+        #[arg(long)]
+        mirror_global: bool,
+        /// List the commits that would be processed and an estimated duration, without calling the LLM
+        #[arg(long, conflicts_with_all = ["refresh_stale", "retry_failed", "verify", "detach"])]
+        dry_run: bool,
+        /// Run the sync in a detached background process that logs to
+        /// .contexthub/logs/hook.log, and return at once (used by the git hooks)
+        #[arg(long, conflicts_with = "verify")]
+        detach: bool,
     },
+    #[command(group = clap::ArgGroup::new("exporting").args(["export", "export_all"]))]
     Context {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Export format (or custom:<name> for an export.custom command); without a value, uses export.default_format
+        #[arg(short, long, num_args = 0..=1)]
+        export: Option<Option<String>>,
+        /// Same as `contexthub export --all`
+        #[arg(long)]
+        export_all: bool,
+        /// Only rewrite a marked ContextHub block in the export file, keeping the rest
+        #[arg(long, requires = "exporting")]
+        append: bool,
+        /// Write the export to this file instead of stdout or the format's default file
+        #[arg(short, long, requires = "export", conflicts_with = "export_all")]
+        output: Option<PathBuf>,
+        /// Collapse near-identical summaries in the export
+        #[arg(long, requires = "exporting")]
+        dedup: bool,
+        /// Only export entries of at least this impact (low, medium, high)
+        #[arg(long, requires = "exporting", value_parser = ["low", "medium", "high"])]
+        min_impact: Option<String>,
+        /// Order exported entries by date (newest first) or impact (high to low, then date)
+        #[arg(long, requires = "exporting", value_parser = ["date", "impact"])]
+        order: Option<String>,
+        /// Only show entries whose summary or message matches this regex
+        #[arg(long)]
+        grep: Option<String>,
+        /// Only show entries whose author contains this name (case-insensitive)
+        #[arg(long)]
+        author: Option<String>,
+        /// Only show entries tagged with this name (`#name` or `Context: name` in the message)
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only show or export entries processed on this branch
+        #[arg(long)]
+        branch: Option<String>,
+        /// Print one `<hash> <date> <summary>` line per entry
+        #[arg(long)]
+        oneline: bool,
+        /// Maximum entries to print or export (0 = unlimited; default from config)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Print how often each extracted technology appears across entries
+        #[arg(long, conflicts_with_all = ["exporting", "grep", "author", "tag", "branch", "oneline"])]
+        technologies: bool,
+        /// List entries mirrored into the global store from every repository
+        #[arg(long, conflicts_with_all = ["exporting", "author", "branch", "technologies"])]
+        global: bool,
+        #[command(subcommand)]
+        subcommand: Option<ContextCommands>,
+    },
+    /// Export stored context; `--all` writes every assistant file and configured custom export
+    #[command(group = clap::ArgGroup::new("target").args(["format", "all"]).required(true))]
+    Export {
+        /// Export format (as for `context --export`)
+        format: Option<String>,
         #[arg(short, long)]
-        export: Option<String>,
+        path: Option<PathBuf>,
+        /// Write CLAUDE.md, .cursorrules, copilot-instructions.md and every
+        /// export.custom command that has an output file, in one pass
+        #[arg(long)]
+        all: bool,
+        /// Only rewrite a marked ContextHub block in the export file, keeping the rest
+        #[arg(long)]
+        append: bool,
+        /// Write the export to this file instead of stdout or the format's default file
+        #[arg(short, long, conflicts_with = "all")]
+        output: Option<PathBuf>,
+        /// Collapse near-identical summaries in the export
+        #[arg(long)]
+        dedup: bool,
+        /// Only export entries processed on this branch
+        #[arg(long)]
+        branch: Option<String>,
+        /// Maximum entries to export (0 = unlimited; default from config)
+        #[arg(long)]
+        limit: Option<usize>,
     },
     Memory {
         #[arg(short, long)]
@@ -58,10 +195,121 @@ enum Commands {
     Doctor {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Back up a corrupt database and prune entries for missing commits
+        #[arg(long)]
+        fix: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
     Status {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Stable `key value` lines for scripts
+        #[arg(long)]
+        porcelain: bool,
+    },
+    Vacuum {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+    /// Manage the cache of LLM results in .contexthub/cache/
+    Cache {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        #[command(subcommand)]
+        subcommand: CacheCommands,
+    },
+    Uninstall {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Remove only the hook and config, keeping the context database
+        #[arg(long)]
+        keep_data: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Show the ContextHub log (.contexthub/logs/contexthub.log)
+    Log {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Number of trailing lines to print (default: 50)
+        #[arg(short = 'n', long)]
+        lines: Option<usize>,
+        /// Keep printing new lines as they are written
+        #[arg(short, long)]
+        follow: bool,
+        /// Show the output of hook-started syncs (.contexthub/logs/hook.log) instead
+        #[arg(long)]
+        hook: bool,
+    },
+    /// Rebuild stored entries
+    Reindex {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Re-run extraction on stored diffs (needs context.store_diffs) with the current model and prompt
+        #[arg(long)]
+        reprompt: bool,
+        /// Entries to process concurrently
+        #[arg(short, long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
+    },
+    Summarize {
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Base revision (default: origin/HEAD, else the branch's upstream,
+        /// else main or master)
+        #[arg(long)]
+        base: Option<String>,
+        #[arg(long, default_value = "HEAD")]
+        target: String,
+    },
+    /// Show the stored context for one file's history, oldest first
+    File {
+        /// File or directory, relative to the repository root
+        file: String,
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Also ask the LLM for an overview of how the file evolved
+        #[arg(long)]
+        overview: bool,
+    },
+    /// Delete the stored entry for one commit (full hash or unique prefix)
+    Remove {
+        commit: String,
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+    /// Full-text search over commit messages, summaries and extracted context
+    Search {
+        /// Words to look for; every word must appear in the entry
+        #[arg(required = true)]
+        query: Vec<String>,
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Maximum matches to print (0 = unlimited; default from config)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Rank entries by meaning using embeddings from ollama.embedding_model
+        /// instead of matching words
+        #[arg(long)]
+        semantic: bool,
+        /// Search the entries mirrored into the global store from every repository
+        #[arg(long, conflicts_with = "semantic")]
+        global: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContextCommands {
+    /// Show a single entry in full, including the model and prompt version
+    Show {
+        hash: String,
+    },
+    /// Correct an entry's summary in $EDITOR; the edit survives reindex and re-sync
+    Edit {
+        hash: String,
     },
 }
 
@@ -78,17 +326,58 @@ enum MemoryCommands {
 #[derive(Subcommand)]
 enum ConfigCommands {
     Show {},
+    /// Print a single value by dotted key, e.g. `ollama.model`. Secrets
+    /// such as `ollama.api_key` are masked
+    Get {
+        key: String,
+        /// Print secrets in full instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
     SetModel {
         model: String,
     },
     SetOllamaUrl {
         url: String,
     },
+    /// Number of commits `sync` processes when no range is given
+    SetRange {
+        range: usize,
+    },
+    /// Ignore whitespace-only changes in diffs (true/false)
+    SetIgnoreWhitespace {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+    /// Project description shown as "## Overview" at the top of exports ("" clears it)
+    SetOverview {
+        #[arg(required_unless_present = "file", conflicts_with = "file")]
+        text: Option<String>,
+        /// Read the description from a file, e.g. a longer markdown document
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Customize the extraction prompt in .contexthub/prompt.md, starting from the built-in one
+    EditPrompt,
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Delete every cached result, so the next sync asks the LLM again
+    Clear,
 }
 
 #[derive(Subcommand)]
 enum HookCommands {
-    Install,
+    Install {
+        /// Sync after every commit, or for the pushed commits at push time
+        #[arg(long = "type", default_value = "post-commit", value_parser = ["post-commit", "pre-push"])]
+        hook_type: String,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rewrite the ContextHub hook to call the current binary
+    Repair,
     Uninstall,
 }
 
@@ -96,73 +385,241 @@ fn get_repo_path(path: Option<PathBuf>) -> PathBuf {
     path.unwrap_or_else(|| std::env::current_dir().unwrap())
 }
 
-fn load_config(path: &PathBuf) -> Result<utils::config::Config> {
-    utils::config::Config::load(path)
+/// Load the repository config, or the `--config` file when one was given
+fn load_config(path: &Path, config_file: Option<&PathBuf>) -> Result<utils::config::Config> {
+    match config_file {
+        Some(file) => utils::config::Config::load_from(file),
+        None => utils::config::Config::load(path),
+    }
 }
 
-/// Guard: ensures contexthub is initialized before running a command
-fn require_init(path: &PathBuf) -> Result<()> {
-    if !commands::init::is_initialized(path) {
-        anyhow::bail!(
-            "ContextHub is not initialized in this directory.\nRun 'contexthub init' first."
-        );
+/// Load config with the one-shot `--endpoint` override applied. Only used by
+/// commands that talk to Ollama and never save the config back.
+fn load_llm_config(
+    path: &Path,
+    config_file: Option<&PathBuf>,
+    endpoint: Option<&str>,
+) -> Result<utils::config::Config> {
+    let mut config = load_config(path, config_file)?;
+    if let Some(url) = endpoint {
+        config.ollama.endpoint = url.to_string();
+    }
+    Ok(config)
+}
+
+/// Guard: ensures contexthub is initialized before running a command. With
+/// `--config` the settings live elsewhere, so only `.contexthub/` is required.
+fn require_init(path: &Path, config_file: Option<&PathBuf>) -> Result<()> {
+    let initialized = match config_file {
+        Some(_) => path.join(".contexthub").is_dir(),
+        None => commands::init::is_initialized(path),
+    };
+    if !initialized {
+        return Err(ContextHubError::NotInitialized.into());
     }
     Ok(())
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(utils::error::exit_code(&e));
+    }
+}
 
+async fn run(cli: Cli) -> Result<()> {
     // Initialize logger — writes to .contexthub/logs/ if initialized, else stderr
     let log_path = {
         let repo = get_repo_path(None);
-        let lp = repo.join(".contexthub/logs/contexthub.log");
+        let lp = utils::logger::get_log_path(&repo);
         if lp.parent().map(|p| p.exists()).unwrap_or(false) {
             Some(lp)
         } else {
             None
         }
     };
-    let _ = utils::logger::init_logger(log_path);
+    let _ = utils::logger::init_logger(log_path, cli.verbose);
 
     log::info!("contexthub started: {:?}", std::env::args().collect::<Vec<_>>());
 
+    if let Some(url) = &cli.endpoint {
+        reqwest::Url::parse(url)
+            .map_err(|e| ContextHubError::Config(format!("Invalid --endpoint '{}': {}", url, e)))?;
+    }
+    let endpoint = cli.endpoint.as_deref();
+    let config_file = cli.config.as_ref();
+
     match cli.command {
-        Commands::Init { path } => {
+        Commands::Init {
+            path,
+            non_interactive,
+            model,
+            no_hook,
+            no_sync,
+        } => {
             let repo_path = get_repo_path(path);
-            commands::init::init_repo(&repo_path).await?;
+            let options = commands::init::InitOptions {
+                non_interactive,
+                model,
+                no_hook,
+                no_sync,
+            };
+            commands::init::init_repo(&repo_path, endpoint, config_file, &options).await?;
         }
 
-        Commands::Sync { path, from, last } => {
+        Commands::Sync {
+            path,
+            from,
+            since,
+            last,
+            skip_patterns,
+            include_submodules,
+            first_parent,
+            refresh_stale,
+            retry_failed,
+            verify,
+            stream,
+            no_cache,
+            jobs,
+            mirror_global,
+            dry_run,
+            detach,
+        } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
-            let config = load_config(&repo_path)?;
+            require_init(&repo_path, config_file)?;
+            if detach {
+                commands::sync::spawn_detached(&repo_path)?;
+                return Ok(());
+            }
+            let mut config = load_llm_config(&repo_path, config_file, endpoint)?;
+            config.git.include_submodules |= include_submodules;
+            config.git.first_parent |= first_parent;
+            config.ollama.stream |= stream;
+            config.ollama.cache &= !no_cache;
+            config.context.mirror_to_global |= mirror_global;
+            let jobs = usize::from(jobs);
+            if jobs > 1 && config.context.effective_chaining_depth() > 0 {
+                // Concurrent commits can't each see the summary of the one before
+                println!("Note: --jobs {} turns off incremental chaining for this sync", jobs);
+                config.context.incremental_chaining = false;
+            }
+            let since = since.as_deref().map(commands::sync::parse_since).transpose()?;
+            if dry_run {
+                return commands::sync::dry_run(&repo_path, &config, from, since, last, &skip_patterns);
+            }
             // Clean up expired TTL entries before syncing
             let storage = core::storage::Storage::new(&repo_path.join(".contexthub/context.db"))?;
+            commands::sync::check_fingerprint(&repo_path, &storage)?;
             let expired = storage.cleanup_expired_ttl()?;
             if expired > 0 {
                 println!("Cleaned up {} expired TTL entries", expired);
             }
-            commands::sync::sync_context(&repo_path, &config, from, last).await?;
+            if verify {
+                commands::sync::verify_stored(&repo_path, &storage)?;
+            }
+            let synced = if retry_failed {
+                commands::sync::retry_failed(&repo_path, &config).await
+            } else if refresh_stale {
+                commands::sync::refresh_stale(&repo_path, &config, jobs).await
+            } else {
+                commands::sync::sync_context(&repo_path, &config, from, since, last, &skip_patterns, jobs).await
+            };
+            // The commits that did succeed are stored, so exports still follow them
+            let partial = synced.as_ref().is_err_and(|e| {
+                matches!(e.downcast_ref(), Some(utils::error::ContextHubError::SyncFailures(_)))
+            });
+            if synced.is_ok() || partial {
+                commands::context::auto_export(&repo_path, &config);
+                commands::sync::mirror_to_global(&repo_path, &config);
+            }
+            synced?;
         }
 
-        Commands::Context { path, export } => {
+        Commands::Context {
+            path,
+            export,
+            export_all,
+            append,
+            output,
+            dedup,
+            min_impact,
+            order,
+            grep,
+            author,
+            tag,
+            branch,
+            oneline,
+            limit,
+            technologies,
+            global,
+            subcommand,
+        } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
-            let config = load_config(&repo_path)?;
+            if global && subcommand.is_none() {
+                let config = load_config(&repo_path, config_file)?;
+                return commands::context::display_global_context(
+                    &config,
+                    grep.as_deref(),
+                    tag.as_deref(),
+                    oneline,
+                    limit,
+                );
+            }
+            require_init(&repo_path, config_file)?;
+            let mut config = load_config(&repo_path, config_file)?;
+            config.export.dedup |= dedup;
+            if let Some(level) = min_impact {
+                config.export.min_impact = level;
+            }
+            if let Some(order) = order {
+                config.export.order = order;
+            }
             
-            if let Some(format) = export {
-                commands::context::export_context(&repo_path, &config, &format)?;
+            if let Some(ContextCommands::Show { hash }) = subcommand {
+                commands::context::show_context(&repo_path, &config, &hash)?;
+            } else if let Some(ContextCommands::Edit { hash }) = subcommand {
+                commands::context::edit_context(&repo_path, &config, &hash)?;
+            } else if technologies {
+                commands::context::show_technologies(&repo_path, &config)?;
+            } else if export_all {
+                commands::context::export_all(&repo_path, &config, branch, append, limit)?;
+            } else if let Some(format) = export {
+                let format = format.unwrap_or_else(|| config.export.default_format.clone());
+                commands::context::export_context(&repo_path, &config, &format, branch, append, limit, output)?;
             } else {
-                commands::context::display_context(&repo_path, &config)?;
+                commands::context::display_context(
+                    &repo_path,
+                    &config,
+                    &commands::context::ContextFilter {
+                        grep: grep.as_deref(),
+                        author: author.as_deref(),
+                        tag: tag.as_deref(),
+                        branch: branch.as_deref(),
+                    },
+                    oneline,
+                    limit,
+                )?;
+            }
+        }
+
+        Commands::Export { format, path, all, append, output, dedup, branch, limit } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let mut config = load_config(&repo_path, config_file)?;
+            config.export.dedup |= dedup;
+            if all {
+                commands::context::export_all(&repo_path, &config, branch, append, limit)?;
+            } else if let Some(format) = format {
+                commands::context::export_context(&repo_path, &config, &format, branch, append, limit, output)?;
             }
         }
 
         Commands::Memory { path, subcommand } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
-            let mut config = load_config(&repo_path)?;
+            require_init(&repo_path, config_file)?;
+            let mut config = load_config(&repo_path, config_file)?;
             
             match subcommand {
                 Some(MemoryCommands::Ttl { clear, set_ttl }) => {
@@ -182,19 +639,34 @@ async fn main() -> Result<()> {
 
         Commands::Config { path, subcommand } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
-            let mut config = load_config(&repo_path)?;
+            require_init(&repo_path, config_file)?;
+            let mut config = load_config(&repo_path, config_file)?;
             
             match subcommand {
                 Some(ConfigCommands::Show { }) => {
                     commands::config_cmd::show_config(&config)?;
                 }
+                Some(ConfigCommands::Get { key, reveal }) => {
+                    commands::config_cmd::get_config_value(&config, &key, reveal)?;
+                }
                 Some(ConfigCommands::SetModel { model }) => {
                     commands::config_cmd::set_config_model(&repo_path, &mut config, model)?;
                 }
                 Some(ConfigCommands::SetOllamaUrl { url }) => {
                     commands::config_cmd::set_config_ollama_url(&repo_path, &mut config, url)?;
                 }
+                Some(ConfigCommands::SetRange { range }) => {
+                    commands::config_cmd::set_config_range(&repo_path, &mut config, range)?;
+                }
+                Some(ConfigCommands::SetIgnoreWhitespace { enabled }) => {
+                    commands::config_cmd::set_config_ignore_whitespace(&repo_path, &mut config, enabled)?;
+                }
+                Some(ConfigCommands::SetOverview { text, file }) => {
+                    commands::config_cmd::set_config_overview(&repo_path, &mut config, text, file)?;
+                }
+                Some(ConfigCommands::EditPrompt) => {
+                    commands::config_cmd::edit_prompt(&repo_path)?;
+                }
                 None => {
                     commands::config_cmd::show_config(&config)?;
                 }
@@ -203,11 +675,18 @@ async fn main() -> Result<()> {
 
         Commands::Hook { path, command } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
+            require_init(&repo_path, config_file)?;
             
             match command {
-                HookCommands::Install => {
-                    commands::hook::install_hook(&repo_path)?;
+                HookCommands::Install { hook_type, dry_run } => {
+                    if dry_run {
+                        commands::hook::preview_hook(&repo_path, &hook_type)?;
+                    } else {
+                        commands::hook::install_hook(&repo_path, &hook_type)?;
+                    }
+                }
+                HookCommands::Repair => {
+                    commands::hook::repair_hook(&repo_path)?;
                 }
                 HookCommands::Uninstall => {
                     commands::hook::uninstall_hook(&repo_path)?;
@@ -215,17 +694,87 @@ async fn main() -> Result<()> {
             }
         }
 
-        Commands::Doctor { path } => {
+        Commands::Doctor { path, fix, json } => {
+            let repo_path = get_repo_path(path);
+            let config = load_llm_config(&repo_path, config_file, endpoint)?;
+            commands::doctor::doctor(&repo_path, &config, fix, json).await?;
+        }
+
+        Commands::Status { path, porcelain } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let config = load_llm_config(&repo_path, config_file, endpoint)?;
+            commands::sync::get_sync_status(&repo_path, &config, porcelain).await?;
+        }
+
+        Commands::Vacuum { path } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            commands::vacuum::vacuum(&repo_path)?;
+        }
+
+        Commands::Cache { path, subcommand } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            match subcommand {
+                CacheCommands::Clear => commands::cache::clear_cache(&repo_path)?,
+            }
+        }
+
+        Commands::Uninstall { path, keep_data, yes } => {
             let repo_path = get_repo_path(path);
-            let config = load_config(&repo_path)?;
-            commands::doctor::doctor(&repo_path, &config)?;
+            commands::uninstall::uninstall(&repo_path, keep_data, yes)?;
         }
 
-        Commands::Status { path } => {
+        Commands::Log { path, lines, follow, hook } => {
             let repo_path = get_repo_path(path);
-            require_init(&repo_path)?;
-            let config = load_config(&repo_path)?;
-            commands::sync::get_sync_status(&repo_path, &config)?;
+            require_init(&repo_path, config_file)?;
+            commands::log_cmd::show_log(&repo_path, lines, follow, hook)?;
+        }
+
+        Commands::Reindex { path, reprompt, jobs } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let config = load_llm_config(&repo_path, config_file, endpoint)?;
+            commands::reindex::reindex(&repo_path, &config, reprompt, jobs.into()).await?;
+        }
+
+        Commands::Summarize { path, base, target } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let config = load_llm_config(&repo_path, config_file, endpoint)?;
+            commands::summarize::summarize(&repo_path, &config, base.as_deref(), &target).await?;
+        }
+
+        Commands::File { file, path, overview } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let config = load_llm_config(&repo_path, config_file, endpoint)?;
+            commands::file::file_history(&repo_path, &config, &file, overview).await?;
+        }
+
+        Commands::Remove { commit, path } => {
+            let repo_path = get_repo_path(path);
+            require_init(&repo_path, config_file)?;
+            let config = load_config(&repo_path, config_file)?;
+            commands::context::remove_context(&repo_path, &config, &commit)?;
+        }
+
+        Commands::Search { query, path, limit, semantic, global } => {
+            let repo_path = get_repo_path(path);
+            if global {
+                let config = load_config(&repo_path, config_file)?;
+                return commands::search::search_global(&config, &query.join(" "), limit);
+            }
+            require_init(&repo_path, config_file)?;
+            if semantic {
+                // Query embeddings come from Ollama, so `--endpoint` applies
+                let config = load_llm_config(&repo_path, config_file, endpoint)?;
+                commands::search::semantic_search(&repo_path, &config, &query.join(" "), limit).await?;
+            } else {
+                let config = load_config(&repo_path, config_file)?;
+                commands::search::search(&repo_path, &config, &query.join(" "), limit)?;
+            }
         }
     }
 
@@ -3,8 +3,15 @@ pub mod screens;
 
 use ratatui::{backend::CrosstermBackend, Frame, Terminal};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
 
-use screens::context::ContextScreen;
+use crate::core::context::ContextProcessor;
+use crate::core::git::CommitInfo;
+use crate::utils::config::Config;
+
+use screens::context::{ContextAction, ContextScreen};
 use screens::sync::SyncScreen;
 
 pub enum AppState {
@@ -13,6 +20,13 @@ pub enum AppState {
     Exit,
 }
 
+/// Progress reported by the background sync worker
+enum SyncEvent {
+    Done { failed: bool },
+    /// The worker could not start (e.g. the database failed to open)
+    Fatal(String),
+}
+
 pub struct App {
     pub state: AppState,
     pub should_exit: bool,
@@ -26,27 +40,55 @@ impl App {
         }
     }
 
-    pub fn run_sync(commits: Vec<crate::core::git::CommitInfo>) -> io::Result<()> {
-        use crossterm::event::{read, Event, KeyCode};
+    pub fn run_sync(repo_path: &Path, config: &Config, commits: Vec<CommitInfo>) -> io::Result<()> {
+        use crossterm::event::{poll, read, Event, KeyCode};
+        use screens::sync::SyncStatus;
 
         let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend)?;
 
         let mut screen = SyncScreen::new(commits);
+        let mut events: Option<mpsc::Receiver<SyncEvent>> = None;
 
         loop {
+            if let Some(rx) = &events {
+                while let Ok(event) = rx.try_recv() {
+                    match event {
+                        SyncEvent::Done { failed } => screen.commit_done(failed),
+                        SyncEvent::Fatal(e) => screen.fail(e),
+                    }
+                }
+            }
+
             terminal.draw(|f: &mut Frame<'_>| {
                 screen.render(f);
             })?;
 
+            // Poll rather than block so worker progress redraws without a keypress
+            if !poll(Duration::from_millis(100))? {
+                continue;
+            }
             if let Event::Key(key) = read()? {
+                if key.code == KeyCode::Esc {
+                    break;
+                }
+                if screen.status != SyncStatus::Selection {
+                    continue;
+                }
                 match key.code {
-                    KeyCode::Esc => break,
                     KeyCode::Up => screen.move_up(),
                     KeyCode::Down => screen.move_down(),
+                    KeyCode::PageUp => screen.page_up(),
+                    KeyCode::PageDown => screen.page_down(),
+                    KeyCode::Home => screen.move_home(),
+                    KeyCode::End => screen.move_end(),
                     KeyCode::Char(' ') => screen.toggle_selection(),
-                    KeyCode::Enter => {
-                        screen.status = screens::sync::SyncStatus::Processing;
+                    KeyCode::Enter if screen.start_processing() => {
+                        events = Some(spawn_sync_worker(
+                            repo_path.to_path_buf(),
+                            config.clone(),
+                            screen.queue.clone(),
+                        ));
                     }
                     _ => {}
                 }
@@ -56,13 +98,21 @@ impl App {
         Ok(())
     }
 
-    pub fn run_context(contexts: Vec<crate::core::storage::GlobalContext>) -> io::Result<()> {
-        use crossterm::event::{read, Event, KeyCode};
+    pub fn run_context(
+        repo_path: &Path,
+        processor: &crate::core::context::ContextProcessor,
+        summary_chars: usize,
+    ) -> io::Result<()> {
+        use crossterm::event::{read, Event};
+
+        let contexts = processor
+            .get_global_context()
+            .map_err(|e| io::Error::other(e.to_string()))?;
 
         let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend)?;
 
-        let mut screen = ContextScreen::new(contexts);
+        let mut screen = ContextScreen::new(contexts, summary_chars);
 
         loop {
             terminal.draw(|f: &mut Frame<'_>| {
@@ -70,11 +120,23 @@ impl App {
             })?;
 
             if let Event::Key(key) = read()? {
-                match key.code {
-                    KeyCode::Esc => break,
-                    KeyCode::Up => screen.move_up(),
-                    KeyCode::Down => screen.move_down(),
-                    _ => {}
+                match screen.handle_key(key.code) {
+                    ContextAction::Exit => break,
+                    ContextAction::Export => {
+                        let visible: Vec<_> = screen.visible().into_iter().cloned().collect();
+                        let out_path = repo_path.join("CLAUDE.md");
+                        screen.status_message = Some(
+                            match crate::core::context::write_export(&out_path, &processor.render_claude(&visible, None), false) {
+                                Ok(_) => format!(
+                                    "✓ Exported {} entries to {}",
+                                    visible.len(),
+                                    out_path.display()
+                                ),
+                                Err(e) => format!("✗ Export failed: {}", e),
+                            },
+                        );
+                    }
+                    ContextAction::None => {}
                 }
             }
         }
@@ -82,3 +144,38 @@ impl App {
         Ok(())
     }
 }
+
+/// Run `process_commit` over `commits` on a separate thread with its own
+/// runtime and database connection, reporting each result. The worker stops
+/// after the current commit once the receiver is dropped.
+fn spawn_sync_worker(
+    repo_path: PathBuf,
+    config: Config,
+    commits: Vec<CommitInfo>,
+) -> mpsc::Receiver<SyncEvent> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let run = || -> anyhow::Result<()> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let processor = ContextProcessor::new(&repo_path, config)?;
+            runtime.block_on(async {
+                for commit in &commits {
+                    let result = processor.process_commit(commit, None).await;
+                    if let Err(e) = &result {
+                        log::error!("  ✗ {} - {}", commit.short_hash, e);
+                    }
+                    if tx.send(SyncEvent::Done { failed: result.is_err() }).is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(())
+        };
+        if let Err(e) = run() {
+            let _ = tx.send(SyncEvent::Fatal(e.to_string()));
+        }
+    });
+    rx
+}
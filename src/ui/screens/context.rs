@@ -1,25 +1,114 @@
+use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
-    widgets::{Block, List, ListItem, Paragraph},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
+use crate::core::context::truncate_summary;
 use crate::core::storage::GlobalContext;
 use crate::ui::components::theme::Theme;
 
+/// What the event loop should do after a key press.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextAction {
+    None,
+    Export,
+    Exit,
+}
+
 pub struct ContextScreen {
     pub contexts: Vec<GlobalContext>,
     pub scroll: u16,
     pub current_index: usize,
+    pub filter: String,
+    pub filter_mode: bool,
+    /// Indices into `contexts` matching `filter`, refreshed as the query changes
+    filtered: Vec<usize>,
+    pub show_detail: bool,
+    pub status_message: Option<String>,
+    /// `ui.display_summary_chars`; the detail pane always shows the full text
+    pub summary_chars: usize,
 }
 
 impl ContextScreen {
-    pub fn new(contexts: Vec<GlobalContext>) -> Self {
+    pub fn new(contexts: Vec<GlobalContext>, summary_chars: usize) -> Self {
+        let filtered = (0..contexts.len()).collect();
         Self {
             contexts,
             scroll: 0,
             current_index: 0,
+            filter: String::new(),
+            filter_mode: false,
+            filtered,
+            show_detail: false,
+            status_message: None,
+            summary_chars,
+        }
+    }
+
+    /// Entries matching the current filter (case-insensitive, summary or message)
+    pub fn visible(&self) -> Vec<&GlobalContext> {
+        self.filtered.iter().map(|&i| &self.contexts[i]).collect()
+    }
+
+    /// Recompute `filtered` for the current query, keeping the selected entry
+    /// selected if it still matches.
+    fn refilter(&mut self) {
+        let selected = self.filtered.get(self.current_index).copied();
+        let query = self.filter.to_lowercase();
+        self.filtered = self
+            .contexts
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                query.is_empty()
+                    || c.context_summary.to_lowercase().contains(&query)
+                    || c.commit_message.to_lowercase().contains(&query)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.current_index = selected
+            .and_then(|s| self.filtered.iter().position(|&i| i == s))
+            .unwrap_or(0);
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> ContextAction {
+        if self.filter_mode {
+            match code {
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.filter_mode = false;
+                }
+                KeyCode::Enter => self.filter_mode = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return ContextAction::None,
+            }
+            self.refilter();
+            return ContextAction::None;
         }
+
+        self.status_message = None;
+        match code {
+            KeyCode::Esc if self.show_detail => self.show_detail = false,
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.refilter();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return ContextAction::Exit,
+            KeyCode::Up => self.move_up(),
+            KeyCode::Down => self.move_down(),
+            KeyCode::Enter => self.show_detail = !self.show_detail,
+            KeyCode::Char('/') => self.filter_mode = true,
+            KeyCode::Char('e') => return ContextAction::Export,
+            _ => {}
+        }
+        ContextAction::None
     }
 
     pub fn render(&self, f: &mut Frame<'_>) {
@@ -30,7 +119,7 @@ impl ContextScreen {
 
         if self.contexts.is_empty() {
             let empty = Paragraph::new(
-                "No context stored.\nRun 'ctxhub sync' to extract context from commits.",
+                "No context stored.\nRun 'contexthub sync' to extract context from commits.",
             )
             .style(theme.muted_style())
             .alignment(ratatui::layout::Alignment::Center)
@@ -48,37 +137,141 @@ impl ContextScreen {
             ])
             .split(size);
 
-        let title = Paragraph::new("Repository Context")
-            .style(theme.primary_style())
-            .block(Block::default().borders(Borders::ALL));
+        let visible = self.visible();
+
+        let mut title = format!(
+            "Repository Context ({} of {})",
+            visible.len(),
+            self.contexts.len()
+        );
+        if !self.filter.is_empty() && !self.filter_mode {
+            title.push_str(&format!("  filter: \"{}\"", self.filter));
+        }
+        let title = Paragraph::new(title)
+        .style(theme.primary_style())
+        .block(Block::default().borders(Borders::ALL));
         f.render_widget(title, chunks[0]);
 
-        let items: Vec<ListItem> = self
-            .contexts
+        let body = if self.show_detail {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .split(chunks[1])
+        } else {
+            Layout::default()
+                .constraints([Constraint::Percentage(100)])
+                .split(chunks[1])
+        };
+
+        let items: Vec<ListItem> = visible
             .iter()
-            .enumerate()
-            .map(|(_i, c)| {
+            .map(|c| {
                 let msg = c.commit_message.lines().next().unwrap_or("No message");
-                ListItem::new(format!(
-                    "{} - {}",
-                    &c.commit_hash[..7.min(c.commit_hash.len())],
-                    msg
-                ))
+                let mut first = vec![Span::raw(format!(
+                    "{} - ",
+                    &c.commit_hash[..7.min(c.commit_hash.len())]
+                ))];
+                first.extend(self.highlighted(msg, &theme));
+                let mut second = vec![Span::raw("  ")];
+                second.extend(self.highlighted(
+                    &truncate_summary(&c.context_summary, self.summary_chars),
+                    &theme,
+                ));
+                ListItem::new(vec![Line::from(first), Line::from(second)])
             })
             .collect();
 
         let list = List::new(items)
             .block(Block::default().title("Commits").borders(Borders::ALL))
-            .style(theme.default_style());
+            .style(theme.default_style())
+            .highlight_style(theme.accent_style());
 
-        f.render_widget(list, chunks[1]);
+        let mut state = ListState::default();
+        if !visible.is_empty() {
+            state.select(Some(self.current_index.min(visible.len() - 1)));
+        }
+        f.render_stateful_widget(list, body[0], &mut state);
 
-        let hint = Paragraph::new("Press ESC to exit")
-            .style(theme.muted_style())
+        if self.show_detail {
+            let detail = visible
+                .get(self.current_index)
+                .map(|c| Self::detail_text(c))
+                .unwrap_or_default();
+            let pane = Paragraph::new(detail)
+                .style(theme.default_style())
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title("Detail").borders(Borders::ALL));
+            f.render_widget(pane, body[1]);
+        }
+
+        let (footer, style) = if self.filter_mode {
+            (format!("Filter: {}_", self.filter), theme.primary_style())
+        } else if let Some(msg) = &self.status_message {
+            (msg.clone(), theme.accent_style())
+        } else {
+            let esc = if self.filter.is_empty() { "ESC Exit" } else { "ESC Clear filter" };
+            (
+                format!("ENTER Detail  / Filter  e Export CLAUDE.md  {}", esc),
+                theme.muted_style(),
+            )
+        };
+        let hint = Paragraph::new(footer)
+            .style(style)
             .alignment(ratatui::layout::Alignment::Center);
         f.render_widget(hint, chunks[2]);
     }
 
+    /// Split `text` into spans with case-insensitive matches of the filter
+    /// emphasized, so it's visible why an entry is listed.
+    fn highlighted(&self, text: &str, theme: &Theme) -> Vec<Span<'static>> {
+        let query = self.filter.to_lowercase();
+        let lower = text.to_lowercase();
+        // Lowercasing can change byte lengths; only highlight when it didn't
+        if query.is_empty() || lower.len() != text.len() {
+            return vec![Span::raw(text.to_string())];
+        }
+
+        let mut spans = Vec::new();
+        let mut rest = 0;
+        for (start, _) in lower.match_indices(&query) {
+            let end = start + query.len();
+            if start < rest || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                continue;
+            }
+            spans.push(Span::raw(text[rest..start].to_string()));
+            spans.push(Span::styled(
+                text[start..end].to_string(),
+                theme.warning_style().add_modifier(Modifier::BOLD),
+            ));
+            rest = end;
+        }
+        spans.push(Span::raw(text[rest..].to_string()));
+        spans
+    }
+
+    fn detail_text(ctx: &GlobalContext) -> String {
+        let mut text = format!(
+            "{}\n{}\n\n{}\n",
+            ctx.commit_hash,
+            ctx.commit_date.format("%Y-%m-%d %H:%M"),
+            ctx.context_summary
+        );
+        if let Some(impact) = &ctx.impact {
+            text.push_str(&format!("\nImpact: {}\n", impact));
+            for detail in ctx.key_details.iter().flatten() {
+                text.push_str(&format!("- {}\n", detail));
+            }
+            if let Some(technologies) = ctx.technologies.as_ref().filter(|t| !t.is_empty()) {
+                text.push_str(&format!("\nTechnologies: {}\n", technologies.join(", ")));
+            }
+        }
+        let files: Vec<String> = serde_json::from_str(&ctx.files_changed).unwrap_or_default();
+        if !files.is_empty() {
+            text.push_str(&format!("\nFiles: {}\n", files.join(", ")));
+        }
+        text
+    }
+
     pub fn move_up(&mut self) {
         if self.current_index > 0 {
             self.current_index -= 1;
@@ -86,7 +279,7 @@ impl ContextScreen {
     }
 
     pub fn move_down(&mut self) {
-        if self.current_index < self.contexts.len() - 1 {
+        if self.current_index + 1 < self.filtered.len() {
             self.current_index += 1;
         }
     }
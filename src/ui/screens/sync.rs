@@ -13,7 +13,15 @@ pub struct SyncScreen {
     pub current_index: usize,
     pub scroll: u16,
     pub status: SyncStatus,
+    /// Commits being processed, fixed when processing starts
+    pub queue: Vec<CommitInfo>,
+    /// How many of `queue` have finished, successfully or not
     pub processing_index: usize,
+    pub failed: usize,
+    /// Why processing stopped, for `SyncStatus::Error`
+    pub error: Option<String>,
+    /// Rows visible in the commit list, measured on the last render
+    pub page_size: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -32,11 +40,15 @@ impl SyncScreen {
             current_index: 0,
             scroll: 0,
             status: SyncStatus::Selection,
+            queue: Vec::new(),
             processing_index: 0,
+            failed: 0,
+            error: None,
+            page_size: 10,
         }
     }
 
-    pub fn render(&self, f: &mut Frame<'_>) {
+    pub fn render(&mut self, f: &mut Frame<'_>) {
         let theme = Theme::tokyo_night();
         let size = f.area();
 
@@ -48,7 +60,7 @@ impl SyncScreen {
         }
     }
 
-    fn render_selection(&self, f: &mut Frame<'_>, size: ratatui::layout::Rect, theme: &Theme) {
+    fn render_selection(&mut self, f: &mut Frame<'_>, size: ratatui::layout::Rect, theme: &Theme) {
         use ratatui::widgets::Borders;
 
         let chunks = Layout::default()
@@ -65,10 +77,16 @@ impl SyncScreen {
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(title, chunks[0]);
 
+        // Inner height of the bordered list block
+        self.page_size = (chunks[1].height.saturating_sub(2) as usize).max(1);
+        self.ensure_visible();
+
         let items: Vec<ListItem> = self
             .commits
             .iter()
             .enumerate()
+            .skip(self.scroll as usize)
+            .take(self.page_size)
             .map(|(i, c)| {
                 let prefix = if self.selected_indices.contains(&i) {
                     "◉"
@@ -80,7 +98,12 @@ impl SyncScreen {
                     c.short_hash,
                     c.message.lines().next().unwrap_or("")
                 );
-                ListItem::new(format!("{} {}", prefix, line))
+                let item = ListItem::new(format!("{} {}", prefix, line));
+                if i == self.current_index {
+                    item.style(theme.accent_style())
+                } else {
+                    item
+                }
             })
             .collect();
 
@@ -95,7 +118,7 @@ impl SyncScreen {
 
         f.render_widget(list, chunks[1]);
 
-        let hint = Paragraph::new("SPACE Select  ENTER Process  ESC Cancel")
+        let hint = Paragraph::new("SPACE Select  PGUP/PGDN Page  HOME/END Jump  ENTER Process  ESC Cancel")
             .style(theme.muted_style())
             .alignment(ratatui::layout::Alignment::Center);
         f.render_widget(hint, chunks[2]);
@@ -118,26 +141,25 @@ impl SyncScreen {
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(title, chunks[0]);
 
-        let progress = if self.commits.is_empty() {
+        let progress = if self.queue.is_empty() {
             0.0
         } else {
-            self.processing_index as f64 / self.commits.len() as f64
+            self.processing_index as f64 / self.queue.len() as f64
         };
 
         let progress_bar = Gauge::default()
             .ratio(progress)
             .label(format!(
                 "{}/{}",
-                self.processing_index + 1,
-                self.commits.len()
+                (self.processing_index + 1).min(self.queue.len()),
+                self.queue.len()
             ))
             .style(theme.accent_style())
             .block(Block::default().title("Progress").borders(Borders::ALL));
 
         f.render_widget(progress_bar, chunks[2]);
 
-        if self.processing_index < self.commits.len() {
-            let commit = &self.commits[self.processing_index];
+        if let Some(commit) = self.queue.get(self.processing_index) {
             let info = Paragraph::new(format!(
                 "Processing: {} - {}",
                 commit.short_hash,
@@ -151,7 +173,15 @@ impl SyncScreen {
     fn render_complete(&self, f: &mut Frame<'_>, size: ratatui::layout::Rect, theme: &Theme) {
         use ratatui::widgets::Borders;
 
-        let msg = format!("Processed {} commits successfully!", self.commits.len());
+        let msg = if self.failed == 0 {
+            format!("Processed {} commits successfully!", self.queue.len())
+        } else {
+            format!(
+                "Processed {} commits, {} failed (see the log). Press ESC to exit.",
+                self.queue.len(),
+                self.failed
+            )
+        };
         let paragraph = Paragraph::new(msg)
             .style(theme.accent_style())
             .alignment(ratatui::layout::Alignment::Center)
@@ -166,7 +196,11 @@ impl SyncScreen {
     fn render_error(&self, f: &mut Frame<'_>, size: ratatui::layout::Rect, theme: &Theme) {
         use ratatui::widgets::Borders;
 
-        let paragraph = Paragraph::new("Error processing commits.")
+        let msg = match &self.error {
+            Some(e) => format!("Error processing commits: {}", e),
+            None => "Error processing commits.".to_string(),
+        };
+        let paragraph = Paragraph::new(msg)
             .style(theme.error_style())
             .alignment(ratatui::layout::Alignment::Center)
             .block(
@@ -178,20 +212,46 @@ impl SyncScreen {
     }
 
     pub fn move_up(&mut self) {
-        if self.current_index > 0 {
-            self.current_index -= 1;
-            if self.current_index < self.scroll as usize {
-                self.scroll = self.current_index as u16;
-            }
-        }
+        self.current_index = self.current_index.saturating_sub(1);
+        self.ensure_visible();
     }
 
     pub fn move_down(&mut self) {
-        if self.current_index < self.commits.len() - 1 {
-            self.current_index += 1;
-            if self.current_index >= self.scroll as usize + 10 {
-                self.scroll += 1;
-            }
+        self.current_index = (self.current_index + 1).min(self.last_index());
+        self.ensure_visible();
+    }
+
+    pub fn page_up(&mut self) {
+        self.current_index = self.current_index.saturating_sub(self.page_size);
+        self.ensure_visible();
+    }
+
+    pub fn page_down(&mut self) {
+        self.current_index = (self.current_index + self.page_size).min(self.last_index());
+        self.ensure_visible();
+    }
+
+    pub fn move_home(&mut self) {
+        self.current_index = 0;
+        self.ensure_visible();
+    }
+
+    pub fn move_end(&mut self) {
+        self.current_index = self.last_index();
+        self.ensure_visible();
+    }
+
+    fn last_index(&self) -> usize {
+        self.commits.len().saturating_sub(1)
+    }
+
+    /// Adjust `scroll` so `current_index` falls inside the visible page
+    fn ensure_visible(&mut self) {
+        let scroll = self.scroll as usize;
+        if self.current_index < scroll {
+            self.scroll = self.current_index as u16;
+        } else if self.current_index >= scroll + self.page_size {
+            self.scroll = (self.current_index + 1 - self.page_size) as u16;
         }
     }
 
@@ -207,6 +267,35 @@ impl SyncScreen {
         }
     }
 
+    /// Leave selection and process the selected commits. Returns `false` if
+    /// nothing is selected.
+    pub fn start_processing(&mut self) -> bool {
+        self.queue = self.get_selected_commits();
+        if self.queue.is_empty() {
+            return false;
+        }
+        self.processing_index = 0;
+        self.failed = 0;
+        self.status = SyncStatus::Processing;
+        true
+    }
+
+    /// Record one finished commit; moves to `Complete` after the last
+    pub fn commit_done(&mut self, failed: bool) {
+        self.processing_index += 1;
+        if failed {
+            self.failed += 1;
+        }
+        if self.processing_index >= self.queue.len() {
+            self.status = SyncStatus::Complete;
+        }
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+        self.status = SyncStatus::Error;
+    }
+
     pub fn get_selected_commits(&self) -> Vec<CommitInfo> {
         self.selected_indices
             .iter()
@@ -1,31 +1,155 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::utils::error::ContextHubError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OllamaConfig {
+    /// API spoken by `endpoint`: `ollama`, or `openai` for any
+    /// OpenAI-compatible server (LM Studio, llama.cpp, OpenAI, OpenRouter)
+    pub provider: String,
     pub endpoint: String,
     pub model: String,
     pub temperature: f32,
     pub max_tokens: usize,
+    /// Response budget for per-commit extraction. Falls back to `max_tokens`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extraction_max_tokens: Option<usize>,
+    /// Response budget for multi-commit overviews. Falls back to `max_tokens`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overview_max_tokens: Option<usize>,
+    /// Model context window (`num_ctx`) in tokens. Sent to Ollama when set;
+    /// prompts estimated to exceed it are split into several requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<usize>,
+    /// Nucleus sampling cutoff. Sent only when set; the model's default
+    /// applies otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Penalty for repeated tokens (Ollama only). Sent only when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+    /// Proxy for all Ollama requests. When unset, `HTTP_PROXY`/`HTTPS_PROXY`
+    /// from the environment are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// INSECURE: skip TLS certificate verification, e.g. for a self-signed
+    /// internal inference server.
+    pub accept_invalid_certs: bool,
+    /// Bearer token for the `openai` provider. `CONTEXTHUB_API_KEY` is used
+    /// when unset, which keeps the key out of config.json.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Stream `/api/generate` output and show progress on stderr while a
+    /// commit is analyzed (also `sync --stream`)
+    pub stream: bool,
+    /// Seconds an LLM request may take (with `stream`, the longest gap
+    /// between chunks); 0 waits forever
+    pub timeout_secs: u64,
+    /// Extra attempts after a timeout, connection error or 5xx/429 response,
+    /// with exponential backoff. Other 4xx responses are not retried.
+    pub max_retries: u32,
+    /// Reuse results stored in `.contexthub/cache/` for an identical prompt
+    /// instead of calling the model again (`sync --no-cache` skips it)
+    pub cache: bool,
+    /// Model that turns stored entries into vectors for `search --semantic`
+    /// (empty disables embeddings)
+    pub embedding_model: String,
+    /// Ask Ollama for JSON-only output (`format: "json"`) when extracting
+    pub force_json: bool,
 }
 
 impl Default for OllamaConfig {
     fn default() -> Self {
         Self {
+            provider: "ollama".to_string(),
             endpoint: "http://localhost:11434".to_string(),
             model: "llama3.2".to_string(),
             temperature: 0.3,
             max_tokens: 2048,
+            extraction_max_tokens: None,
+            overview_max_tokens: None,
+            num_ctx: None,
+            top_p: None,
+            repeat_penalty: None,
+            proxy_url: None,
+            accept_invalid_certs: false,
+            api_key: None,
+            stream: false,
+            timeout_secs: 120,
+            max_retries: 2,
+            cache: true,
+            embedding_model: "nomic-embed-text".to_string(),
+            force_json: true,
         }
     }
 }
 
+impl OllamaConfig {
+    pub fn extraction_max_tokens(&self) -> usize {
+        self.extraction_max_tokens.unwrap_or(self.max_tokens)
+    }
+
+    pub fn overview_max_tokens(&self) -> usize {
+        self.overview_max_tokens.unwrap_or(self.max_tokens)
+    }
+
+    /// `num_ctx`, or Ollama's own default window when unset
+    pub fn context_window(&self) -> usize {
+        self.num_ctx.unwrap_or(2048)
+    }
+
+    /// `api_key`, falling back to the `CONTEXTHUB_API_KEY` environment variable
+    pub fn resolved_api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("CONTEXTHUB_API_KEY").ok())
+            .filter(|key| !key.trim().is_empty())
+    }
+}
+
+/// Values of `ollama.provider`
+pub const PROVIDERS: &[&str] = &["ollama", "openai"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ContextConfig {
     pub default_commit_range: usize,
     pub max_tokens_per_commit: usize,
+    /// Diff bytes sent to the LLM for one commit (0 = `max_tokens_per_commit`
+    /// × 4). Oversized diffs are cut per file.
+    pub max_diff_bytes: usize,
     pub global_retention_days: i32,
     pub ttl_days: i32,
+    /// Regexes matched against commit messages; matching commits are not synced.
+    pub skip_message_patterns: Vec<String>,
+    /// Number of previous summaries fed to the LLM for continuity (0 disables).
+    pub chaining_depth: usize,
+    /// Feed previous summaries to the LLM at all; off gives independent
+    /// per-commit summaries whatever `chaining_depth` says
+    pub incremental_chaining: bool,
+    /// Diffs read ahead on a background thread while the LLM works (0 = fetch inline).
+    pub diff_prefetch: usize,
+    /// Keep each commit's preprocessed (truncated) diff in the database so
+    /// `reindex --reprompt` can re-run extraction without git.
+    pub store_diffs: bool,
+    /// Longest commit message sent to the LLM, cut at a line boundary
+    /// (0 = unlimited). The stored message is always complete.
+    pub max_message_chars: usize,
+    /// Exports only include entries processed on the current branch
+    /// (override with `context --branch`)
+    pub isolate_by_branch: bool,
+    /// Globs of repository paths left out of the diffs sent to the LLM,
+    /// e.g. vendored code and build output
+    pub ignore_globs: Vec<String>,
+    /// Hand-written project description put at the top of the markdown and
+    /// assistant exports as "## Overview" (empty = no section)
+    pub project_overview: String,
+    /// After each sync, copy high-impact entries into the cross-repository
+    /// store (see `global_db_path`), tagged with the repository name
+    pub mirror_to_global: bool,
 }
 
 impl Default for ContextConfig {
@@ -33,16 +157,69 @@ impl Default for ContextConfig {
         Self {
             default_commit_range: 10,
             max_tokens_per_commit: 1000,
+            max_diff_bytes: 32 * 1024,
             global_retention_days: -1,
             ttl_days: 7,
+            skip_message_patterns: vec!["^Merge ".to_string(), "^Bump ".to_string()],
+            chaining_depth: 1,
+            incremental_chaining: true,
+            diff_prefetch: 4,
+            store_diffs: false,
+            max_message_chars: 4000,
+            isolate_by_branch: false,
+            ignore_globs: vec![
+                "**/node_modules/**".to_string(),
+                "**/vendor/**".to_string(),
+                "**/target/**".to_string(),
+                "**/dist/**".to_string(),
+            ],
+            project_overview: String::new(),
+            mirror_to_global: false,
+        }
+    }
+}
+
+impl ContextConfig {
+    /// `chaining_depth`, or 0 when `incremental_chaining` is off
+    pub fn effective_chaining_depth(&self) -> usize {
+        if self.incremental_chaining {
+            self.chaining_depth
+        } else {
+            0
+        }
+    }
+
+    /// Diff bytes per commit: `max_diff_bytes`, or `max_tokens_per_commit` × 4
+    /// when that is 0
+    pub fn diff_byte_budget(&self) -> usize {
+        match self.max_diff_bytes {
+            0 => self.max_tokens_per_commit.saturating_mul(4),
+            limit => limit,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GitConfig {
     pub auto_sync: bool,
     pub hook_enabled: bool,
+    /// Unchanged lines shown around each diff hunk. Fewer lines keep prompts
+    /// small for token-constrained models; more give the LLM extra surrounding
+    /// code to reason about.
+    pub diff_context_lines: u32,
+    /// Summarize the submodule commits behind each gitlink bump instead of
+    /// leaving the LLM an opaque "Subproject commit" line.
+    pub include_submodules: bool,
+    /// Drop whitespace-only hunks from diffs; commits that only reformat
+    /// code are stored without calling the LLM.
+    pub ignore_whitespace: bool,
+    /// Follow only first parents when walking history (`git log --first-parent`),
+    /// skipping commits that came in through merged side branches.
+    pub first_parent: bool,
+    /// Leave merge commits out of syncs; their diff against the first parent
+    /// repeats the merged branch's commits.
+    pub skip_merge_commits: bool,
 }
 
 impl Default for GitConfig {
@@ -50,57 +227,319 @@ impl Default for GitConfig {
         Self {
             auto_sync: false,
             hook_enabled: false,
+            diff_context_lines: 3,
+            include_submodules: false,
+            ignore_whitespace: false,
+            first_parent: false,
+            skip_merge_commits: true,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UiConfig {
     pub theme: String,
+    /// Summaries longer than this are cut with "…" in list views (0 = never).
+    /// Display only; stored summaries are untouched.
+    pub display_summary_chars: usize,
+    /// ASCII-only `init` wizard output without colors or box drawing
+    /// (also enabled by the `NO_COLOR` environment variable)
+    pub plain_prompts: bool,
 }
 
 impl Default for UiConfig {
     fn default() -> Self {
         Self {
             theme: "tokyo-night".to_string(),
+            display_summary_chars: 160,
+            plain_prompts: false,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub ollama: OllamaConfig,
-    pub context: ContextConfig,
-    pub git: GitConfig,
-    pub ui: UiConfig,
+#[serde(default)]
+pub struct ExportConfig {
+    /// Format used by `context --export` when no value is given
+    pub default_format: String,
+    /// Entries per export when no format-specific cap is set
+    pub max_entries: usize,
+    pub claude_max: Option<usize>,
+    pub cursor_max: Option<usize>,
+    pub copilot_max: Option<usize>,
+    /// Add an "Author" line to each entry in the markdown export
+    pub include_author: bool,
+    /// Collapse near-identical summaries in exports (also `context --export --dedup`)
+    pub dedup: bool,
+    /// Trigram Jaccard similarity (0.0-1.0) at which two summaries count as duplicates
+    pub dedup_threshold: f64,
+    /// Folder for the `obsidian` export (one note per commit); relative
+    /// paths are resolved against the repository root
+    pub obsidian_dir: String,
+    /// Blocks emitted by the claude/cursor/copilot exports, in `EXPORT_SECTIONS` order
+    pub sections: Vec<String>,
+    /// Leave out entries whose extracted impact ranks below this
+    /// (`low` keeps everything; entries without a known impact are kept)
+    pub min_impact: String,
+    /// Entry order in exports: `date` (newest first) or `impact` (high to
+    /// low, newest first within a level)
+    pub order: String,
+    /// Assistant exports regenerated after every successful `sync`
+    pub auto_export: Vec<String>,
+    /// Add a "Generated" timestamp line to the assistant exports. Off by
+    /// default so regenerating unchanged context leaves the files untouched.
+    pub timestamp: bool,
+    /// External exporters, used as `context --export custom:<name>`
+    pub custom: BTreeMap<String, CustomExporter>,
+}
+
+/// A user-defined export format: `command` is run through the shell with the
+/// exported entries as JSON on stdin, and its stdout becomes the export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomExporter {
+    pub command: String,
+    /// File the output is written to, relative to the repository root.
+    /// When unset, the output is printed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
 }
 
-impl Default for Config {
+impl Default for ExportConfig {
     fn default() -> Self {
         Self {
-            ollama: OllamaConfig::default(),
-            context: ContextConfig::default(),
-            git: GitConfig::default(),
-            ui: UiConfig::default(),
+            default_format: "markdown".to_string(),
+            max_entries: 20,
+            claude_max: Some(30),
+            cursor_max: None,
+            copilot_max: Some(15),
+            include_author: false,
+            dedup: false,
+            dedup_threshold: 0.8,
+            obsidian_dir: "ContextHub".to_string(),
+            sections: EXPORT_SECTIONS.iter().map(|s| s.to_string()).collect(),
+            min_impact: "low".to_string(),
+            order: "date".to_string(),
+            auto_export: Vec::new(),
+            timestamp: false,
+            custom: BTreeMap::new(),
         }
     }
 }
 
+/// Sections the assistant exports can include. `notes` is the content of
+/// `.contexthub/notes.md`, if present.
+pub const EXPORT_SECTIONS: &[&str] = &["overview", "files", "recent_changes", "technologies", "notes"];
+
+/// Values of the extracted `impact` field, least significant first
+pub const IMPACT_LEVELS: &[&str] = &["low", "medium", "high"];
+
+/// Values of `export.order`
+pub const EXPORT_ORDERS: &[&str] = &["date", "impact"];
+
+/// Formats `export.auto_export` accepts: the ones written to a file
+pub const AUTO_EXPORT_FORMATS: &[&str] = &["claude", "cursor", "cursorrules", "copilot", "github-copilot"];
+
+/// Every name `context --export` accepts, including aliases
+pub const EXPORT_FORMATS: &[&str] = &[
+    "markdown", "md", "json", "ndjson", "claude", "cursor", "cursorrules", "copilot", "github-copilot",
+    "obsidian",
+];
+
+impl ExportConfig {
+    pub fn claude_max(&self) -> usize {
+        self.claude_max.unwrap_or(self.max_entries)
+    }
+
+    pub fn cursor_max(&self) -> usize {
+        self.cursor_max.unwrap_or(self.max_entries)
+    }
+
+    pub fn copilot_max(&self) -> usize {
+        self.copilot_max.unwrap_or(self.max_entries)
+    }
+
+    /// Built-in format names followed by `custom:<name>` for each configured exporter
+    pub fn format_names(&self) -> Vec<String> {
+        EXPORT_FORMATS
+            .iter()
+            .map(|f| f.to_string())
+            .chain(self.custom.keys().map(|name| format!("custom:{}", name)))
+            .collect()
+    }
+}
+
+/// The cross-repository store: `$XDG_CONFIG_HOME/contexthub/global.db`,
+/// or `~/.config/contexthub/global.db` when that isn't set
+pub fn global_db_path() -> anyhow::Result<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })
+        .ok_or_else(|| {
+            ContextHubError::Config("Cannot find the home directory for the global store; set HOME or XDG_CONFIG_HOME".to_string())
+        })?;
+    Ok(config_home.join("contexthub").join("global.db"))
+}
+
+/// Entry cap shared by `context` and every export: an explicit `--limit`
+/// wins over the config default, and `0` in either place means unlimited.
+pub fn resolve_limit(flag: Option<usize>, config_default: usize) -> usize {
+    match flag.unwrap_or(config_default) {
+        0 => usize::MAX,
+        n => n,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub ollama: OllamaConfig,
+    pub context: ContextConfig,
+    pub git: GitConfig,
+    pub ui: UiConfig,
+    pub export: ExportConfig,
+    /// File given with `--config`; `save` writes back there instead of the repo
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+}
+
 impl Config {
-    pub fn load(repo_path: &PathBuf) -> anyhow::Result<Self> {
+    pub fn load(repo_path: &Path) -> anyhow::Result<Self> {
         let config_path = repo_path.join(".contexthub/config.json");
 
         if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            let config: Config = serde_json::from_str(&content)?;
-            Ok(config)
+            Self::parse(&config_path)
         } else {
             Ok(Config::default())
         }
     }
 
-    pub fn save(&self, repo_path: &PathBuf) -> anyhow::Result<()> {
-        let config_path = repo_path.join(".contexthub/config.json");
+    /// Load an explicit config file (`--config`). Unlike `load`, a missing
+    /// file is an error rather than a silent fallback to defaults.
+    pub fn load_from(file: &PathBuf) -> anyhow::Result<Self> {
+        if !file.exists() {
+            return Err(ContextHubError::Config(format!(
+                "Config file not found: {}",
+                file.display()
+            ))
+            .into());
+        }
+        let mut config = Self::parse(file)?;
+        config.file = Some(file.clone());
+        Ok(config)
+    }
+
+    fn parse(config_path: &PathBuf) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(config_path)?;
+        let config: Config = serde_json::from_str(&content).map_err(|e| {
+            ContextHubError::Config(format!("Invalid {}: {}", config_path.display(), e))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ContextHubError> {
+        if !PROVIDERS.contains(&self.ollama.provider.as_str()) {
+            return Err(ContextHubError::Config(format!(
+                "Invalid ollama.provider '{}'. Supported: {}",
+                self.ollama.provider,
+                PROVIDERS.join(", ")
+            )));
+        }
+        let default_format = self.export.default_format.as_str();
+        let known_custom = default_format
+            .strip_prefix("custom:")
+            .is_some_and(|name| self.export.custom.contains_key(name));
+        if !EXPORT_FORMATS.contains(&default_format) && !known_custom {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.default_format '{}'. Supported: {}",
+                self.export.default_format,
+                self.export.format_names().join(", ")
+            )));
+        }
+        if let Some((glob, e)) = self
+            .context
+            .ignore_globs
+            .iter()
+            .find_map(|g| glob::Pattern::new(g).err().map(|e| (g, e)))
+        {
+            return Err(ContextHubError::Config(format!(
+                "Invalid context.ignore_globs pattern '{}': {}",
+                glob, e
+            )));
+        }
+        if let Some((name, _)) = self
+            .export
+            .custom
+            .iter()
+            .find(|(name, exporter)| name.is_empty() || exporter.command.trim().is_empty())
+        {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.custom entry '{}': a name and a command are required",
+                name
+            )));
+        }
+        if let Some(unknown) = self
+            .export
+            .sections
+            .iter()
+            .find(|s| !EXPORT_SECTIONS.contains(&s.as_str()))
+        {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.sections entry '{}'. Supported: {}",
+                unknown,
+                EXPORT_SECTIONS.join(", ")
+            )));
+        }
+        if !IMPACT_LEVELS.contains(&self.export.min_impact.as_str()) {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.min_impact '{}'. Supported: {}",
+                self.export.min_impact,
+                IMPACT_LEVELS.join(", ")
+            )));
+        }
+        if let Some(unknown) = self
+            .export
+            .auto_export
+            .iter()
+            .find(|f| !AUTO_EXPORT_FORMATS.contains(&f.as_str()))
+        {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.auto_export entry '{}'. Supported: {}",
+                unknown,
+                AUTO_EXPORT_FORMATS.join(", ")
+            )));
+        }
+        if !EXPORT_ORDERS.contains(&self.export.order.as_str()) {
+            return Err(ContextHubError::Config(format!(
+                "Invalid export.order '{}'. Supported: {}",
+                self.export.order,
+                EXPORT_ORDERS.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Look up a dotted key such as `ollama.model`. `None` if no such key.
+    pub fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let mut value = serde_json::to_value(self).ok()?;
+        for part in key.split('.') {
+            value = value.as_object_mut()?.remove(part)?;
+        }
+        Some(value)
+    }
+
+    pub fn save(&self, repo_path: &Path) -> anyhow::Result<()> {
+        let config_path = match &self.file {
+            Some(file) => file.clone(),
+            None => repo_path.join(".contexthub/config.json"),
+        };
         let content = serde_json::to_string_pretty(self)?;
         std::fs::write(config_path, content)?;
         Ok(())
@@ -114,7 +553,56 @@ impl Config {
         self.ollama.endpoint = url;
     }
 
+    pub fn set_commit_range(&mut self, range: usize) {
+        self.context.default_commit_range = range;
+    }
+
+    pub fn set_ignore_whitespace(&mut self, enabled: bool) {
+        self.git.ignore_whitespace = enabled;
+    }
+
+    pub fn set_project_overview(&mut self, overview: String) {
+        self.context.project_overview = overview;
+    }
+
     pub fn set_ttl_days(&mut self, days: i32) {
         self.context.ttl_days = days;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_limit_zero_means_unlimited() {
+        assert_eq!(resolve_limit(Some(0), 50), usize::MAX);
+        assert_eq!(resolve_limit(None, 0), usize::MAX);
+    }
+
+    #[test]
+    fn resolve_limit_falls_back_to_config_default() {
+        assert_eq!(resolve_limit(None, 50), 50);
+    }
+
+    #[test]
+    fn resolve_limit_prefers_explicit_flag() {
+        assert_eq!(resolve_limit(Some(10), 50), 10);
+    }
+
+    #[test]
+    fn incremental_chaining_off_disables_chaining() {
+        let mut context = ContextConfig { chaining_depth: 3, ..ContextConfig::default() };
+        assert_eq!(context.effective_chaining_depth(), 3);
+        context.incremental_chaining = false;
+        assert_eq!(context.effective_chaining_depth(), 0);
+    }
+
+    #[test]
+    fn max_diff_bytes_is_not_capped_by_the_token_budget() {
+        let mut context = ContextConfig { max_tokens_per_commit: 1000, max_diff_bytes: 32 * 1024, ..ContextConfig::default() };
+        assert_eq!(context.diff_byte_budget(), 32 * 1024);
+        context.max_diff_bytes = 0;
+        assert_eq!(context.diff_byte_budget(), 4000);
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// Failures with a dedicated process exit code. Everything else still flows
+/// through `anyhow` and exits with 1.
+#[derive(Debug)]
+pub enum ContextHubError {
+    NotInitialized,
+    OllamaUnavailable,
+    Config(String),
+    /// Some commits in a sync failed; the rest were stored
+    SyncFailures(usize),
+}
+
+impl fmt::Display for ContextHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => write!(
+                f,
+                "ContextHub is not initialized in this directory.\nRun 'contexthub init' first."
+            ),
+            Self::OllamaUnavailable => write!(
+                f,
+                "Ollama is not running. Please start Ollama first:\n  ollama serve"
+            ),
+            Self::Config(msg) => write!(f, "{}", msg),
+            Self::SyncFailures(count) => write!(
+                f,
+                "{} commit(s) failed to sync; the others were stored",
+                count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ContextHubError {}
+
+/// Exit codes are part of the CLI contract (see README "Exit codes").
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<ContextHubError>() {
+            return match e {
+                ContextHubError::NotInitialized => 2,
+                ContextHubError::OllamaUnavailable => 3,
+                ContextHubError::Config(_) => 5,
+                ContextHubError::SyncFailures(_) => 6,
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if e.is_connect() || e.is_timeout() {
+                return 3;
+            }
+        }
+        if cause.is::<git2::Error>() {
+            return 4;
+        }
+    }
+    1
+}
@@ -1,13 +1,23 @@
 use log::LevelFilter;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub fn init_logger(log_path: Option<PathBuf>) -> anyhow::Result<()> {
+/// `verbosity` comes from the repeated `-v` flag: 0 logs at info, 1 enables
+/// debug output (including LLM prompts/responses) for ContextHub itself, and
+/// 2+ enables trace output for every crate.
+pub fn init_logger(log_path: Option<PathBuf>, verbosity: u8) -> anyhow::Result<()> {
     let mut builder = env_logger::Builder::new();
 
+    match verbosity {
+        0 => builder.filter_level(LevelFilter::Info),
+        1 => builder
+            .filter_level(LevelFilter::Info)
+            .filter_module("contexthub", LevelFilter::Debug),
+        _ => builder.filter_level(LevelFilter::Trace),
+    };
+
     builder
-        .filter_level(LevelFilter::Info)
         .format(|buf, record| {
             writeln!(
                 buf,
@@ -28,7 +38,12 @@ pub fn init_logger(log_path: Option<PathBuf>) -> anyhow::Result<()> {
     Ok(())
 }
 
-#[allow(dead_code)]
-pub fn get_log_path(repo_path: &PathBuf) -> PathBuf {
+pub fn get_log_path(repo_path: &Path) -> PathBuf {
     repo_path.join(".contexthub/logs/contexthub.log")
 }
+
+/// Output of the syncs started by the git hooks, including errors from
+/// before logging is set up (e.g. a missing binary)
+pub fn get_hook_log_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(".contexthub/logs/hook.log")
+}
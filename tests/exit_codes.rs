@@ -0,0 +1,144 @@
+//! The exit codes documented in README "Exit codes" are part of the CLI
+//! contract; these run the real binary and check them.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// An endpoint nothing listens on
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+fn contexthub(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_contexthub"))
+        .args(args)
+        .current_dir(dir)
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .expect("failed to run contexthub")
+}
+
+/// A git repository with one commit
+fn git_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("a.txt")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Add a.txt", &tree, &[]).unwrap();
+    dir
+}
+
+/// A git repository with `.contexthub/` set up, without contacting a model
+fn initialized_repo() -> tempfile::TempDir {
+    let dir = git_repo();
+    let out = contexthub(
+        dir.path(),
+        &["--endpoint", UNREACHABLE, "init", "--non-interactive", "--no-hook", "--no-sync", "--model", "llama3.2"],
+    );
+    assert_eq!(out.status.code(), Some(0), "init failed: {}", String::from_utf8_lossy(&out.stderr));
+    dir
+}
+
+/// A minimal Ollama that lists a model but rejects every generate request,
+/// so each commit of a sync fails. Returns its base URL.
+fn rejecting_ollama() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            let _ = reader.read_exact(&mut body);
+
+            let (status, body) = if request_line.starts_with("GET /api/tags") {
+                ("200 OK", r#"{"models":[{"name":"llama3.2:latest"}]}"#)
+            } else {
+                ("400 Bad Request", r#"{"error":"rejected"}"#)
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+        }
+    });
+    url
+}
+
+#[test]
+fn success_exits_0() {
+    let repo = initialized_repo();
+    let out = contexthub(repo.path(), &["status"]);
+    assert_eq!(out.status.code(), Some(0));
+}
+
+#[test]
+fn uninitialized_directory_exits_2() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = contexthub(dir.path(), &["status"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn unreachable_ollama_exits_3() {
+    let repo = initialized_repo();
+    let out = contexthub(repo.path(), &["--endpoint", UNREACHABLE, "sync"]);
+    assert_eq!(out.status.code(), Some(3));
+}
+
+#[test]
+fn git_error_exits_4() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = contexthub(
+        dir.path(),
+        &["--endpoint", UNREACHABLE, "init", "--non-interactive", "--no-hook", "--no-sync", "--model", "llama3.2"],
+    );
+    assert_eq!(out.status.code(), Some(4), "init outside a git repository");
+
+    let repo = initialized_repo();
+    let out = contexthub(repo.path(), &["sync", "--from", "no-such-revision"]);
+    assert_eq!(out.status.code(), Some(4), "unknown revision");
+}
+
+#[test]
+fn invalid_option_exits_5() {
+    let repo = initialized_repo();
+    let out = contexthub(repo.path(), &["--endpoint", "not a url", "sync"]);
+    assert_eq!(out.status.code(), Some(5));
+}
+
+#[test]
+fn failed_sync_exits_6() {
+    let repo = initialized_repo();
+    let endpoint = rejecting_ollama();
+    let out = contexthub(repo.path(), &["--endpoint", &endpoint, "sync"]);
+    assert_eq!(
+        out.status.code(),
+        Some(6),
+        "stdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&out.stdout),
+        String::from_utf8_lossy(&out.stderr)
+    );
+}